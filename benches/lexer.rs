@@ -0,0 +1,43 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lpp_rs::{Keywords, Lexer};
+
+const PROGRAM: &str = include_str!("../fixtures/bench/program.lpp");
+const TOKENS: &str = include_str!("../fixtures/tokens/tokens.lpp");
+
+fn count_tokens(source: &str) -> u64 {
+  Lexer::new(&source).count() as u64
+}
+
+fn lex_fixtures(c: &mut Criterion) {
+  let mut group = c.benchmark_group("lexer");
+  for (name, fixture) in [("program", PROGRAM), ("tokens", TOKENS)] {
+    for copies in [10, 1_000] {
+      let source = fixture.repeat(copies);
+      group.throughput(Throughput::Elements(count_tokens(&source)));
+      group.bench_with_input(BenchmarkId::new(name, copies), &source, |b, source| {
+        b.iter(|| Lexer::new(&source.as_str()).for_each(|token| drop(black_box(token))))
+      });
+    }
+  }
+  group.finish();
+}
+
+fn lex_spanish(c: &mut Criterion) {
+  let source = PROGRAM
+    .replace("let ", "variable ")
+    .replace("fn(", "procedimiento(")
+    .replace("return ", "regresa ")
+    .repeat(1_000);
+  let mut group = c.benchmark_group("lexer");
+  group.throughput(Throughput::Elements(count_tokens(&source)));
+  group.bench_function("spanish_keywords", |b| {
+    b.iter(|| {
+      let lexer = Lexer::with_keywords(&source.as_str(), Keywords::spanish());
+      lexer.for_each(|token| drop(black_box(token)))
+    })
+  });
+  group.finish();
+}
+
+criterion_group!(benches, lex_fixtures, lex_spanish);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lpp_rs::{Lexer, Parser};
+
+/// `depth` nested `if` expressions, each with an `else` block
+fn nested_ifs(depth: usize) -> String {
+  let mut source = String::new();
+  for _ in 0..depth {
+    source.push_str("if (x < 10) { let x = x + 1; ");
+  }
+  source.push_str("x;");
+  for _ in 0..depth {
+    source.push_str(" } else { x; }");
+  }
+  source
+}
+
+/// `depth` nested array literals, each with a sibling element
+fn nested_arrays(depth: usize) -> String {
+  let mut source = "[1, ".repeat(depth);
+  source.push('x');
+  source.push_str(&"]".repeat(depth));
+  source.push(';');
+  source
+}
+
+/// `depth` nested blocks, each with a `let` before the inner one
+fn nested_blocks(depth: usize) -> String {
+  let mut source = "{ let x = x + 1; ".repeat(depth);
+  source.push_str("x;");
+  source.push_str(&" }".repeat(depth));
+  source
+}
+
+/// `depth` nested calls, each with a sibling argument
+fn nested_calls(depth: usize) -> String {
+  let mut source = "f(1, ".repeat(depth);
+  source.push('x');
+  source.push_str(&")".repeat(depth));
+  source.push(';');
+  source
+}
+
+/// Builds the source to parse for a given nesting depth
+type SourceOf = fn(usize) -> String;
+
+fn parse_nested(c: &mut Criterion) {
+  let mut group = c.benchmark_group("parser");
+  let cases: [(&str, SourceOf); 4] = [
+    ("nested_ifs", nested_ifs),
+    ("nested_arrays", nested_arrays),
+    ("nested_blocks", nested_blocks),
+    ("nested_calls", nested_calls),
+  ];
+  for (name, source_of) in cases {
+    // each `if` takes two of the `MAX_NESTING_DEPTH` levels, its expression
+    // and its block, so deeper input would only measure the error
+    for depth in [10, 30, 60] {
+      let source = source_of(depth);
+      group.throughput(Throughput::Elements(depth as u64));
+      group.bench_with_input(BenchmarkId::new(name, depth), &source, |b, source| {
+        b.iter(|| {
+          let mut parser = Parser::new(Lexer::new(&source.as_str()));
+          black_box(parser.parse_program())
+        })
+      });
+    }
+  }
+  group.finish();
+}
+
+criterion_group!(benches, parse_nested);
+criterion_main!(benches);
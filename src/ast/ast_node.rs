@@ -1,5 +1,7 @@
 use enum_dispatch::enum_dispatch;
 
+use crate::lexer::Span;
+
 use super::Token;
 
 impl Token {
@@ -17,15 +19,25 @@ pub trait NodeDisplay: Sized {
 #[enum_dispatch]
 pub trait AstNode: NodeDisplay {
   fn token_literal<'s>(&self, source: &'s str) -> &'s str;
+  /// Source range covered by the whole node
+  fn span(&self) -> Span;
 }
 
+/// Implements `AstNode` for a node with a `token` field. The span defaults to
+/// the token's one and can be given as a closure for compound nodes.
 #[macro_export]
 macro_rules! tokened {
   ($node:ty) => {
+    $crate::tokened!($node, |node: &$node| node.token.span());
+  };
+  ($node:ty, $span:expr) => {
     impl $crate::ast::ast_node::AstNode for $node {
       fn token_literal<'s>(&self, source: &'s str) -> &'s str {
         self.token.literal(source)
       }
+      fn span(&self) -> $crate::lexer::Span {
+        ($span)(self)
+      }
     }
   };
 }
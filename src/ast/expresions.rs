@@ -3,18 +3,20 @@ use std::rc::Rc;
 use dupe::Dupe;
 use enum_dispatch::enum_dispatch;
 
-use crate::tokened;
+use crate::{interner::Symbol, lexer::Span, tokened};
 
 use super::{
   ast_node::{AstNode, NodeDisplay},
   statements::Block,
-  Token,
+  Token, TokenKind,
 };
 
+#[derive(Clone)]
 #[enum_dispatch(NodeDisplay, AstNode)]
 pub enum Expression {
   Ident(Ident),
   Int(Int),
+  Float(Float),
   Prefix(Prefix),
   Infix(Infix),
   Bool(Bool),
@@ -22,14 +24,24 @@ pub enum Expression {
   Func(Func),
   Call(Call),
   StringLiteral(StringLiteral),
+  TemplateString(TemplateString),
+  Array(ArrayLiteral),
+  Hash(HashLiteral),
+  Index(Index),
 }
 
+#[derive(Clone)]
 pub struct Ident {
   token: Token,
+  symbol: Symbol,
 }
 impl Ident {
-  pub fn new(token: Token) -> Ident {
-    Ident { token }
+  pub fn new(token: Token, symbol: Symbol) -> Ident {
+    Ident { token, symbol }
+  }
+  /// Interned name, which is what identifiers are compared by
+  pub fn symbol(&self) -> Symbol {
+    self.symbol
   }
 }
 tokened!(Ident);
@@ -40,14 +52,18 @@ impl NodeDisplay for Ident {
   }
 }
 
+#[derive(Clone)]
 pub struct Int {
   pub(crate) token: Token,
-  pub(crate) value: u32,
+  pub(crate) value: i64,
 }
 impl Int {
-  pub fn new(token: Token, value: u32) -> Int {
+  pub fn new(token: Token, value: i64) -> Int {
     Int { token, value }
   }
+  pub fn value(&self) -> i64 {
+    self.value
+  }
 }
 tokened!(Int);
 impl NodeDisplay for Int {
@@ -57,22 +73,51 @@ impl NodeDisplay for Int {
   }
 }
 
+#[derive(Clone)]
+pub struct Float {
+  pub(crate) token: Token,
+  pub(crate) value: f64,
+}
+impl Float {
+  pub fn new(token: Token, value: f64) -> Float {
+    Float { token, value }
+  }
+  pub fn value(&self) -> f64 {
+    self.value
+  }
+}
+tokened!(Float);
+impl NodeDisplay for Float {
+  fn source_fmt<'s>(&self, _source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    // `{:?}` keeps the `.0` of whole numbers
+    let val = self.value;
+    write!(f, "{val:?}")
+  }
+}
+
 /// Suported cases
 /// - -exp
 /// - !exp
+#[derive(Clone)]
 pub struct Prefix {
   token: Token,
-  rhs: Box<Expression>,
+  rhs: Rc<Expression>,
 }
 impl Prefix {
-  pub fn new(token: Token, rhs: impl Into<Box<Expression>>) -> Prefix {
+  pub fn new(token: Token, rhs: impl Into<Rc<Expression>>) -> Prefix {
     Prefix {
       token,
       rhs: rhs.into(),
     }
   }
+  pub fn parts(&self) -> (TokenKind, &Expression) {
+    (self.token.kind(), &self.rhs)
+  }
 }
-tokened!(Prefix);
+tokened!(Prefix, |node: &Prefix| node
+  .token
+  .span()
+  .join(node.rhs.span()));
 impl NodeDisplay for Prefix {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(f, "{}", self.token.literal(source))?;
@@ -82,32 +127,38 @@ impl NodeDisplay for Prefix {
 }
 
 /// exp1 operator exp2
+#[derive(Clone)]
 pub struct Infix {
   token: Token,
-  lhs: Box<Expression>,
+  lhs: Rc<Expression>,
   operator: String,
-  rhs: Box<Expression>,
+  rhs: Rc<Expression>,
 }
 impl Infix {
   pub fn new(token: Token, lhs: Expression, operator: String, rhs: Expression) -> Infix {
     Infix {
       token,
-      lhs: Box::new(lhs),
+      lhs: Rc::new(lhs),
       operator,
-      rhs: Box::new(rhs),
+      rhs: Rc::new(rhs),
     }
   }
+  pub fn parts(&self) -> (&Expression, TokenKind, &Expression) {
+    (&self.lhs, self.token.kind(), &self.rhs)
+  }
 }
-tokened!(Infix);
+tokened!(Infix, |node: &Infix| node.lhs.span().join(node.rhs.span()));
 impl NodeDisplay for Infix {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "(")?;
     self.lhs.source_fmt(source, f)?;
     write!(f, " {} ", self.operator)?;
     self.rhs.source_fmt(source, f)?;
-    Ok(())
+    write!(f, ")")
   }
 }
 
+#[derive(Clone)]
 pub struct Bool {
   token: Token,
   value: bool,
@@ -128,18 +179,19 @@ impl NodeDisplay for Bool {
   }
 }
 
+#[derive(Clone)]
 pub struct If {
   token: Token,
-  condition: Box<Expression>,
-  consequence: Box<Block>,
-  alternative: Option<Box<Block>>,
+  condition: Rc<Expression>,
+  consequence: Rc<Block>,
+  alternative: Option<Rc<Block>>,
 }
 impl If {
   pub fn new(
     token: Token,
-    condition: Box<Expression>,
-    consequence: Box<Block>,
-    alternative: Option<Box<Block>>,
+    condition: Rc<Expression>,
+    consequence: Rc<Block>,
+    alternative: Option<Rc<Block>>,
   ) -> If {
     If {
       token,
@@ -156,7 +208,10 @@ impl If {
     )
   }
 }
-tokened!(If);
+tokened!(If, |node: &If| {
+  let last = node.alternative.as_ref().unwrap_or(&node.consequence);
+  node.token.span().join(last.span())
+});
 impl NodeDisplay for If {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(f, "if(")?;
@@ -173,68 +228,84 @@ impl NodeDisplay for If {
   }
 }
 
+#[derive(Clone)]
 pub struct Func {
   token: Token,
-  params: Vec<Ident>,
-  body: Option<Block>,
+  params: Rc<[Ident]>,
+  body: Option<Rc<Block>>,
 }
 impl Func {
   pub fn new(token: Token, params: Vec<Ident>, body: Option<Block>) -> Func {
     Func {
       token,
-      params,
-      body,
+      params: params.into(),
+      body: body.map(Rc::new),
     }
   }
+  pub fn params(&self) -> Rc<[Ident]> {
+    self.params.dupe()
+  }
+  pub fn body(&self) -> Option<Rc<Block>> {
+    self.body.dupe()
+  }
 }
-tokened!(Func);
+tokened!(Func, |node: &Func| match &node.body {
+  Some(body) => node.token.span().join(body.span()),
+  None => node.token.span(),
+});
 impl NodeDisplay for Func {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let literal = self.token.literal(source);
     write!(f, "{literal}(")?;
     let mut is_first = true;
-    for param in &self.params {
+    for param in self.params.iter() {
       if !is_first {
         write!(f, ", ")?;
-        is_first = false;
       }
+      is_first = false;
       param.source_fmt(source, f)?;
     }
     write!(f, ") {{")?;
     if let Some(body) = &self.body {
-      todo!()
+      body.source_fmt(source, f)?;
     }
-    write!(f, ") }}")
+    write!(f, "}}")
   }
 }
 
+#[derive(Clone)]
 pub struct Call {
   token: Token,
-  func: Box<Expression>, // TODO: try to avoid Box
-  args: Option<Vec<Expression>>,
+  func: Rc<Expression>,
+  args: Option<Rc<[Expression]>>,
+  end: Token,
 }
 
 impl Call {
-  pub fn new(token: Token, func: Expression, args: Option<Vec<Expression>>) -> Call {
+  pub fn new(token: Token, func: Expression, args: Option<Vec<Expression>>, end: Token) -> Call {
     Call {
       token,
-      func: Box::new(func),
-      args,
+      func: Rc::new(func),
+      args: args.map(Into::into),
+      end,
     }
   }
+  pub fn parts(&self) -> (&Expression, &[Expression]) {
+    (&self.func, self.args.as_deref().unwrap_or_default())
+  }
 }
-tokened!(Call);
+tokened!(Call, |node: &Call| node.func.span().join(node.end.span()));
 impl NodeDisplay for Call {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     self.func.source_fmt(source, f)?;
     write!(f, "(")?;
     if let Some(args) = &self.args {
       let mut is_first = true;
-      for arg in args {
+      for arg in args.iter() {
         if !is_first {
           write!(f, ", ")?;
-          is_first = false;
         }
+        is_first = false;
         arg.source_fmt(source, f)?;
       }
     }
@@ -242,6 +313,7 @@ impl NodeDisplay for Call {
   }
 }
 
+#[derive(Clone)]
 pub struct StringLiteral {
   token: Token,
   value: Rc<str>,
@@ -260,3 +332,157 @@ impl NodeDisplay for StringLiteral {
     write!(f, "{}", self.value)
   }
 }
+
+/// [exp1, exp2, ...]
+#[derive(Clone)]
+pub struct ArrayLiteral {
+  token: Token,
+  elements: Rc<[Expression]>,
+  end: Token,
+}
+impl ArrayLiteral {
+  pub fn new(token: Token, elements: Vec<Expression>, end: Token) -> ArrayLiteral {
+    ArrayLiteral {
+      token,
+      elements: elements.into(),
+      end,
+    }
+  }
+  pub fn elements(&self) -> &[Expression] {
+    &self.elements
+  }
+}
+tokened!(ArrayLiteral, |node: &ArrayLiteral| node
+  .token
+  .span()
+  .join(node.end.span()));
+impl NodeDisplay for ArrayLiteral {
+  fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "[")?;
+    let mut is_first = true;
+    for element in self.elements.iter() {
+      if !is_first {
+        write!(f, ", ")?;
+      }
+      is_first = false;
+      element.source_fmt(source, f)?;
+    }
+    write!(f, "]")
+  }
+}
+
+/// {key1: value1, key2: value2, ...}
+#[derive(Clone)]
+pub struct HashLiteral {
+  token: Token,
+  pairs: Rc<[(Expression, Expression)]>,
+  end: Token,
+}
+impl HashLiteral {
+  pub fn new(token: Token, pairs: Vec<(Expression, Expression)>, end: Token) -> HashLiteral {
+    HashLiteral {
+      token,
+      pairs: pairs.into(),
+      end,
+    }
+  }
+  pub fn pairs(&self) -> &[(Expression, Expression)] {
+    &self.pairs
+  }
+}
+tokened!(HashLiteral, |node: &HashLiteral| node
+  .token
+  .span()
+  .join(node.end.span()));
+impl NodeDisplay for HashLiteral {
+  fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{{")?;
+    let mut is_first = true;
+    for (key, value) in self.pairs.iter() {
+      if !is_first {
+        write!(f, ", ")?;
+      }
+      is_first = false;
+      key.source_fmt(source, f)?;
+      write!(f, ": ")?;
+      value.source_fmt(source, f)?;
+    }
+    write!(f, "}}")
+  }
+}
+
+/// exp1[exp2]
+#[derive(Clone)]
+pub struct Index {
+  token: Token,
+  lhs: Rc<Expression>,
+  index: Rc<Expression>,
+  end: Token,
+}
+impl Index {
+  pub fn new(token: Token, lhs: Expression, index: Expression, end: Token) -> Index {
+    Index {
+      token,
+      lhs: Rc::new(lhs),
+      index: Rc::new(index),
+      end,
+    }
+  }
+  pub fn parts(&self) -> (&Expression, &Expression) {
+    (&self.lhs, &self.index)
+  }
+}
+tokened!(Index, |node: &Index| node.lhs.span().join(node.end.span()));
+impl NodeDisplay for Index {
+  fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "(")?;
+    self.lhs.source_fmt(source, f)?;
+    write!(f, "[")?;
+    self.index.source_fmt(source, f)?;
+    write!(f, "])")
+  }
+}
+
+/// String with interpolated expressions, like `"hola {nombre}"`
+#[derive(Clone)]
+pub struct TemplateString {
+  token: Token,
+  parts: Rc<[TemplatePart]>,
+  end: Token,
+}
+#[derive(Clone)]
+pub enum TemplatePart {
+  Text(Rc<str>),
+  Expression(Expression),
+}
+impl TemplateString {
+  pub fn new(token: Token, parts: Vec<TemplatePart>, end: Token) -> TemplateString {
+    TemplateString {
+      token,
+      parts: parts.into(),
+      end,
+    }
+  }
+  pub fn parts(&self) -> &[TemplatePart] {
+    &self.parts
+  }
+}
+tokened!(TemplateString, |node: &TemplateString| node
+  .token
+  .span()
+  .join(node.end.span()));
+impl NodeDisplay for TemplateString {
+  fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for part in self.parts.iter() {
+      match part {
+        TemplatePart::Text(text) => write!(f, "{text}")?,
+        TemplatePart::Expression(exp) => {
+          write!(f, "{{")?;
+          exp.source_fmt(source, f)?;
+          write!(f, "}}")?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
@@ -1,23 +1,31 @@
+use std::rc::Rc;
+
 use enum_dispatch::enum_dispatch;
 
-use crate::tokened;
+use crate::{lexer::Span, tokened};
 
 use super::{
   ast_node::{AstNode, NodeDisplay},
   Expression, Ident, Token,
 };
 
+#[derive(Clone)]
 pub struct Program {
-  statements: Vec<Statement>,
+  statements: Rc<[Statement]>,
 }
 impl Program {
   pub fn new(statements: Vec<Statement>) -> Program {
-    Program { statements }
+    Program {
+      statements: statements.into(),
+    }
+  }
+  pub fn statements(&self) -> &[Statement] {
+    &self.statements
   }
 }
 impl NodeDisplay for Program {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    for st in &self.statements {
+    for st in self.statements.iter() {
       st.source_fmt(source, f)?;
       write!(f, ";")?;
     }
@@ -32,8 +40,15 @@ impl AstNode for Program {
       self.statements[0].token_literal(source)
     }
   }
+  fn span(&self) -> Span {
+    match (self.statements.first(), self.statements.last()) {
+      (Some(first), Some(last)) => first.span().join(last.span()),
+      _ => Span::default(),
+    }
+  }
 }
 
+#[derive(Clone)]
 #[enum_dispatch(NodeDisplay, AstNode)]
 pub enum Statement {
   Let(LetStatement),
@@ -42,6 +57,7 @@ pub enum Statement {
   Block(Block),
 }
 
+#[derive(Clone)]
 pub struct LetStatement {
   token: Token,
   name: Ident,
@@ -51,8 +67,14 @@ impl LetStatement {
   pub fn new(token: Token, name: Ident, value: Expression) -> LetStatement {
     LetStatement { token, name, value }
   }
+  pub fn parts(&self) -> (&Ident, &Expression) {
+    (&self.name, &self.value)
+  }
 }
-tokened!(LetStatement);
+tokened!(LetStatement, |node: &LetStatement| node
+  .token
+  .span()
+  .join(node.value.span()));
 impl NodeDisplay for LetStatement {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let let_st = self.token_literal(source);
@@ -63,6 +85,7 @@ impl NodeDisplay for LetStatement {
   }
 }
 
+#[derive(Clone)]
 pub struct ReturnStatement {
   token: Token,
   return_exp: Expression,
@@ -71,8 +94,14 @@ impl ReturnStatement {
   pub fn new(token: Token, return_exp: Expression) -> ReturnStatement {
     ReturnStatement { token, return_exp }
   }
+  pub fn return_exp(&self) -> &Expression {
+    &self.return_exp
+  }
 }
-tokened!(ReturnStatement);
+tokened!(ReturnStatement, |node: &ReturnStatement| node
+  .token
+  .span()
+  .join(node.return_exp.span()));
 impl NodeDisplay for ReturnStatement {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let ret = self.token_literal(source);
@@ -81,20 +110,27 @@ impl NodeDisplay for ReturnStatement {
   }
 }
 
+#[derive(Clone)]
 pub struct ExpressionStatement {
-  expression: Box<Expression>,
+  expression: Rc<Expression>,
 }
 impl ExpressionStatement {
   pub fn new(expression: Expression) -> ExpressionStatement {
     ExpressionStatement {
-      expression: Box::new(expression),
+      expression: Rc::new(expression),
     }
   }
+  pub fn expression(&self) -> &Expression {
+    &self.expression
+  }
 }
 impl AstNode for ExpressionStatement {
   fn token_literal<'s>(&self, source: &'s str) -> &'s str {
     self.expression.token_literal(source)
   }
+  fn span(&self) -> Span {
+    self.expression.span()
+  }
 }
 impl NodeDisplay for ExpressionStatement {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -102,24 +138,36 @@ impl NodeDisplay for ExpressionStatement {
   }
 }
 
+#[derive(Clone)]
 pub struct Block {
   token: Token,
-  statements: Vec<Statement>,
+  statements: Rc<[Statement]>,
+  end: Token,
 }
 impl Block {
-  pub fn new(token: Token, statements: Vec<Statement>) -> Block {
-    Block { token, statements }
+  pub fn new(token: Token, statements: Vec<Statement>, end: Token) -> Block {
+    Block {
+      token,
+      statements: statements.into(),
+      end,
+    }
+  }
+  pub fn statements(&self) -> &[Statement] {
+    &self.statements
   }
 }
-tokened!(Block);
+tokened!(Block, |node: &Block| node
+  .token
+  .span()
+  .join(node.end.span()));
 impl NodeDisplay for Block {
   fn source_fmt<'s>(&self, source: &'s str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let mut is_first = true;
-    for st in &self.statements {
+    for st in self.statements.iter() {
       if !is_first {
         write!(f, ";")?;
-        is_first = false;
       }
+      is_first = false;
       st.source_fmt(source, f)?;
     }
     Ok(())
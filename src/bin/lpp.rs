@@ -0,0 +1,164 @@
+use std::io::{self, BufRead, Write};
+
+use lpp_rs::{is_incomplete_input, Evaluator, Object};
+
+const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+
+fn main() -> io::Result<()> {
+  println!("Welcome to Platzi programming language (english version).");
+  println!("Write your sentence to start.");
+
+  let evaluator = Evaluator::new();
+  let mut stdin = io::stdin().lock();
+  let mut stdout = io::stdout();
+  let mut entry = Entry::default();
+
+  loop {
+    let prompt = if entry.is_empty() {
+      PROMPT
+    } else {
+      CONTINUATION_PROMPT
+    };
+    write!(stdout, "{prompt}")?;
+    stdout.flush()?;
+
+    let mut line = String::new();
+    if stdin.read_line(&mut line)? == 0 {
+      break;
+    }
+    if let Some(source) = entry.push_line(&line) {
+      run(&evaluator, &source);
+    }
+  }
+
+  if let Some(source) = entry.finish() {
+    run(&evaluator, &source);
+  }
+  Ok(())
+}
+
+/// Lines of the entry being typed, which is submitted once it's complete.
+/// Two empty lines in a row force the submission of an incomplete entry, so
+/// single empty lines can still be used inside of it.
+#[derive(Debug, Default)]
+struct Entry {
+  buffer: String,
+  after_empty_line: bool,
+}
+
+impl Entry {
+  fn is_empty(&self) -> bool {
+    self.buffer.is_empty()
+  }
+
+  /// Adds `line` to the entry, returning its source when it's ready to run
+  fn push_line(&mut self, line: &str) -> Option<String> {
+    let is_empty_line = line.trim().is_empty();
+    if is_empty_line && self.is_empty() {
+      return None;
+    }
+    self.buffer.push_str(line);
+
+    let force = is_empty_line && self.after_empty_line;
+    self.after_empty_line = is_empty_line;
+    if !force && is_incomplete_input(&self.buffer) {
+      return None;
+    }
+    self.finish()
+  }
+
+  /// Takes the source of the entry as it is, for when the input ends
+  fn finish(&mut self) -> Option<String> {
+    self.after_empty_line = false;
+    let source = std::mem::take(&mut self.buffer);
+    if source.trim().is_empty() {
+      return None;
+    }
+    Some(source)
+  }
+}
+
+fn run(evaluator: &Evaluator, source: &str) {
+  let program = match lpp_rs::parse(source) {
+    Ok(program) => program,
+    Err(errors) => {
+      for error in errors {
+        eprintln!("{error}");
+      }
+      return;
+    }
+  };
+
+  match evaluator.eval(&program, source) {
+    Object::Null => {}
+    result => println!("{result}"),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use lpp_rs::{Evaluator, Object};
+
+  use super::Entry;
+
+  /// Entries submitted while feeding `input` line by line
+  fn entries(input: &str) -> Vec<String> {
+    let mut entry = Entry::default();
+    let mut entries: Vec<_> = input
+      .split_inclusive('\n')
+      .filter_map(|line| entry.push_line(line))
+      .collect();
+    entries.extend(entry.finish());
+    entries
+  }
+
+  #[test]
+  fn readme_session() {
+    let input = "let a = 5;
+let b = 10;
+a + b;
+let mayor_de_edad = fn(edad) {
+     // return edad > 18;
+     if(edad > 18) {
+         return true;
+     } else {
+         return false;
+     }
+ };
+
+mayor_de_edad(20);
+mayor_de_edad(15);
+let sumador = fn(x) {
+
+    return fn(y) {
+        return x + y;
+    };
+
+};
+
+let suma_dos = sumador(2);
+suma_dos(5);
+let suma_cinco = sumador(5);
+suma_cinco(20);
+mayor_de_edad(suma_cinco(20));
+";
+    let evaluator = Evaluator::new();
+    let mut results = Vec::new();
+    for source in entries(input) {
+      let program = lpp_rs::parse(&source).unwrap();
+      match evaluator.eval(&program, &source) {
+        Object::Null => {}
+        result => results.push(result.to_string()),
+      }
+    }
+    assert_eq!(results, ["15", "true", "false", "7", "25", "true"]);
+  }
+
+  #[test]
+  fn two_empty_lines_force_submission() {
+    assert_eq!(entries("f(1,\n\n2);\n"), ["f(1,\n\n2);\n"]);
+    assert_eq!(entries("f(1,\n\n\n2);\n"), ["f(1,\n\n\n", "2);\n"]);
+    assert_eq!(entries("\n\nlet a = {\n"), ["let a = {\n"]);
+  }
+}
@@ -0,0 +1,10 @@
+//! Persistent collections used for LPP's runtime arrays and hashes. Cloning
+//! them is O(1) and updating a clone shares most of the structure with the
+//! original, so values captured by closures or passed to builtins are never
+//! copied as a whole.
+
+mod hash_map;
+mod vector;
+
+pub use hash_map::{HashMap, Iter as HashMapIter};
+pub use vector::{Iter as VectorIter, Vector};
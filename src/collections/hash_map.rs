@@ -0,0 +1,309 @@
+use std::{
+  fmt,
+  hash::{DefaultHasher, Hash, Hasher},
+  rc::Rc,
+};
+
+use dupe::Dupe;
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+/// Persistent hash map: a hash array mapped trie.
+pub struct HashMap<K, V> {
+  root: Rc<Node<K, V>>,
+  len: usize,
+}
+
+/// Entries whose hashes share the bits consumed above the node. `bitmap` has
+/// a bit set for each 5-bit chunk of the hash present in `entries`.
+#[derive(Clone)]
+struct Node<K, V> {
+  bitmap: u32,
+  entries: Vec<Entry<K, V>>,
+}
+
+#[derive(Clone)]
+enum Entry<K, V> {
+  Pair(u64, K, V),
+  /// Pairs whose keys have the same hash
+  Collision(u64, Rc<Vec<(K, V)>>),
+  Node(Rc<Node<K, V>>),
+}
+
+fn hash_of<K: Hash>(key: &K) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  key.hash(&mut hasher);
+  hasher.finish()
+}
+
+/// Bit of the node's bitmap for `hash` and the index of its entry
+fn slot(bitmap: u32, hash: u64, shift: u32) -> (u32, usize) {
+  let bit = 1 << ((hash >> shift) & MASK);
+  (bit, (bitmap & (bit - 1)).count_ones() as usize)
+}
+
+impl<K, V> Node<K, V> {
+  fn empty() -> Node<K, V> {
+    Node {
+      bitmap: 0,
+      entries: Vec::new(),
+    }
+  }
+}
+
+impl<K: Eq, V> Node<K, V> {
+  fn get(&self, hash: u64, shift: u32, key: &K) -> Option<&V> {
+    let (bit, idx) = slot(self.bitmap, hash, shift);
+    if self.bitmap & bit == 0 {
+      return None;
+    }
+    match &self.entries[idx] {
+      Entry::Pair(_, k, v) => (k == key).then_some(v),
+      Entry::Collision(_, pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+      Entry::Node(node) => node.get(hash, shift + BITS, key),
+    }
+  }
+}
+
+impl<K: Eq + Clone, V: Clone> Node<K, V> {
+  /// Returns whether `key` wasn't in the node already
+  fn insert(&mut self, hash: u64, shift: u32, key: K, value: V) -> bool {
+    let (bit, idx) = slot(self.bitmap, hash, shift);
+    if self.bitmap & bit == 0 {
+      self.bitmap |= bit;
+      self.entries.insert(idx, Entry::Pair(hash, key, value));
+      return true;
+    }
+    match &mut self.entries[idx] {
+      Entry::Pair(h, k, v) if *h == hash => {
+        if *k == key {
+          *v = value;
+          return false;
+        }
+        let pairs = vec![(k.clone(), v.clone()), (key, value)];
+        self.entries[idx] = Entry::Collision(hash, Rc::new(pairs));
+        true
+      }
+      Entry::Collision(h, pairs) if *h == hash => {
+        let pairs = Rc::make_mut(pairs);
+        if let Some((_, v)) = pairs.iter_mut().find(|(k, _)| *k == key) {
+          *v = value;
+          return false;
+        }
+        pairs.push((key, value));
+        true
+      }
+      Entry::Node(node) => Rc::make_mut(node).insert(hash, shift + BITS, key, value),
+      // a pair or collision with another hash moves down to a new node
+      entry => {
+        let mut node = Node::empty();
+        let existing = std::mem::replace(entry, Entry::Node(Rc::new(Node::empty())));
+        node.insert_entry(existing, shift + BITS);
+        node.insert(hash, shift + BITS, key, value);
+        self.entries[idx] = Entry::Node(Rc::new(node));
+        true
+      }
+    }
+  }
+
+  /// Adds a pair or collision entry, whose hash isn't in the node yet
+  fn insert_entry(&mut self, entry: Entry<K, V>, shift: u32) {
+    let hash = match &entry {
+      Entry::Pair(hash, ..) | Entry::Collision(hash, _) => *hash,
+      Entry::Node(_) => unreachable!("nodes only hold pairs and collisions of their own"),
+    };
+    let (bit, idx) = slot(self.bitmap, hash, shift);
+    self.bitmap |= bit;
+    self.entries.insert(idx, entry);
+  }
+}
+
+impl<K, V> HashMap<K, V> {
+  pub fn new() -> HashMap<K, V> {
+    HashMap {
+      root: Rc::new(Node::empty()),
+      len: 0,
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Pairs in an order that depends on the hashes of their keys
+  pub fn iter(&self) -> Iter<'_, K, V> {
+    Iter {
+      nodes: vec![self.root.entries.iter()],
+      collision: [].iter(),
+    }
+  }
+}
+
+impl<K: Hash + Eq, V> HashMap<K, V> {
+  pub fn get(&self, key: &K) -> Option<&V> {
+    self.root.get(hash_of(key), 0, key)
+  }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> HashMap<K, V> {
+  /// Adds or replaces the value of `key`
+  pub fn insert(&mut self, key: K, value: V) {
+    let hash = hash_of(&key);
+    if Rc::make_mut(&mut self.root).insert(hash, 0, key, value) {
+      self.len += 1;
+    }
+  }
+}
+
+impl<K, V> Clone for HashMap<K, V> {
+  fn clone(&self) -> Self {
+    HashMap {
+      root: self.root.dupe(),
+      len: self.len,
+    }
+  }
+}
+impl<K, V> Dupe for HashMap<K, V> {}
+
+impl<K, V> Default for HashMap<K, V> {
+  fn default() -> Self {
+    HashMap::new()
+  }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> FromIterator<(K, V)> for HashMap<K, V> {
+  fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+    let mut map = HashMap::new();
+    for (key, value) in iter {
+      map.insert(key, value);
+    }
+    map
+  }
+}
+
+impl<K: Hash + Eq, V: PartialEq> PartialEq for HashMap<K, V> {
+  fn eq(&self, other: &Self) -> bool {
+    self.len == other.len && self.iter().all(|(k, v)| other.get(k) == Some(v))
+  }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for HashMap<K, V> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_map().entries(self.iter()).finish()
+  }
+}
+
+pub struct Iter<'m, K, V> {
+  /// Entries left in each node from the root to the current one
+  nodes: Vec<std::slice::Iter<'m, Entry<K, V>>>,
+  collision: std::slice::Iter<'m, (K, V)>,
+}
+
+impl<'m, K, V> Iterator for Iter<'m, K, V> {
+  type Item = (&'m K, &'m V);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some((k, v)) = self.collision.next() {
+        return Some((k, v));
+      }
+      let Some(entry) = self.nodes.last_mut()?.next() else {
+        self.nodes.pop();
+        continue;
+      };
+      match entry {
+        Entry::Pair(_, k, v) => return Some((k, v)),
+        Entry::Collision(_, pairs) => self.collision = pairs.iter(),
+        Entry::Node(node) => self.nodes.push(node.entries.iter()),
+      }
+    }
+  }
+}
+
+impl<'m, K, V> IntoIterator for &'m HashMap<K, V> {
+  type Item = (&'m K, &'m V);
+  type IntoIter = Iter<'m, K, V>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::{
+    hash::{Hash, Hasher},
+    rc::Rc,
+  };
+
+  use super::HashMap;
+
+  #[test]
+  fn insert_and_get() {
+    let map: HashMap<usize, usize> = (0..5_000).map(|k| (k, k * 2)).collect();
+    assert_eq!(map.len(), 5_000);
+    assert!((0..5_000).all(|k| map.get(&k) == Some(&(k * 2))));
+    assert_eq!(map.get(&5_000), None);
+
+    let mut keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    keys.sort();
+    assert!(keys.into_iter().eq(0..5_000));
+  }
+
+  #[test]
+  fn insert_shares_structure() {
+    let base: HashMap<Rc<str>, i64> = [("uno".into(), 1), ("dos".into(), 2)].into_iter().collect();
+    let mut replaced = base.clone();
+    replaced.insert("uno".into(), 10);
+    let mut added = base.clone();
+    added.insert("tres".into(), 3);
+
+    assert_eq!(base.get(&"uno".into()), Some(&1));
+    assert_eq!(replaced.get(&"uno".into()), Some(&10));
+    assert_eq!(replaced.len(), 2);
+    assert_eq!(added.get(&"tres".into()), Some(&3));
+    assert_eq!(added.len(), 3);
+    assert_eq!(base.get(&"tres".into()), None);
+    assert_ne!(base, replaced);
+    assert_eq!(
+      base,
+      [("dos".into(), 2), ("uno".into(), 1)].into_iter().collect()
+    );
+  }
+
+  /// Key whose hash only depends on its first field
+  #[derive(Clone, Debug, PartialEq, Eq)]
+  struct Colliding(u8, u8);
+  impl Hash for Colliding {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+      self.0.hash(state);
+    }
+  }
+
+  #[test]
+  fn hash_collisions() {
+    let mut map = HashMap::new();
+    for a in 0..20 {
+      for b in 0..3 {
+        map.insert(Colliding(a, b), u32::from(a) * 10 + u32::from(b));
+      }
+    }
+    let snapshot = map.clone();
+    map.insert(Colliding(4, 1), 0);
+    map.insert(Colliding(4, 3), 43);
+
+    assert_eq!(map.len(), 61);
+    assert_eq!(snapshot.len(), 60);
+    assert_eq!(map.get(&Colliding(4, 1)), Some(&0));
+    assert_eq!(snapshot.get(&Colliding(4, 1)), Some(&41));
+    assert_eq!(map.get(&Colliding(4, 3)), Some(&43));
+    assert_eq!(map.get(&Colliding(19, 2)), Some(&192));
+    assert_eq!(map.get(&Colliding(20, 0)), None);
+    assert_eq!(map.iter().count(), 61);
+  }
+}
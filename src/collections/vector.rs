@@ -0,0 +1,237 @@
+use std::{fmt, rc::Rc};
+
+use dupe::Dupe;
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+/// Persistent vector: a trie with 32 children per node.
+pub struct Vector<T> {
+  root: Rc<Node<T>>,
+  /// Bits of an index consumed above the leaves
+  shift: u32,
+  /// Elements in the trie, including the hidden ones
+  len: usize,
+  /// Index of the first visible element
+  start: usize,
+}
+
+#[derive(Clone)]
+enum Node<T> {
+  Branch(Vec<Rc<Node<T>>>),
+  Leaf(Vec<T>),
+}
+
+impl<T> Vector<T> {
+  pub fn new() -> Vector<T> {
+    Vector {
+      root: Rc::new(Node::Leaf(Vec::new())),
+      shift: 0,
+      len: 0,
+      start: 0,
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.len - self.start
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn get(&self, index: usize) -> Option<&T> {
+    if index >= self.len() {
+      return None;
+    }
+    let index = self.start + index;
+    let mut node = &*self.root;
+    let mut shift = self.shift;
+    loop {
+      match node {
+        Node::Branch(children) => {
+          node = &children[(index >> shift) & MASK];
+          shift -= BITS;
+        }
+        Node::Leaf(values) => return values.get(index & MASK),
+      }
+    }
+  }
+
+  pub fn first(&self) -> Option<&T> {
+    self.get(0)
+  }
+
+  /// Every element but the first one, or `None` if there are none
+  pub fn rest(&self) -> Option<Vector<T>> {
+    if self.is_empty() {
+      return None;
+    }
+    let mut rest = self.dupe();
+    rest.start += 1;
+    Some(rest)
+  }
+
+  pub fn iter(&self) -> Iter<'_, T> {
+    Iter {
+      vector: self,
+      index: 0,
+    }
+  }
+}
+
+impl<T: Clone> Vector<T> {
+  /// Appends `value`
+  pub fn push_back(&mut self, value: T) {
+    if self.len == WIDTH << self.shift {
+      let root = self.root.dupe();
+      self.root = Rc::new(Node::Branch(vec![root]));
+      self.shift += BITS;
+    }
+    let mut node = Rc::make_mut(&mut self.root);
+    let mut shift = self.shift;
+    loop {
+      match node {
+        Node::Branch(children) => {
+          let child = (self.len >> shift) & MASK;
+          shift -= BITS;
+          if child == children.len() {
+            let empty = match shift {
+              0 => Node::Leaf(Vec::with_capacity(WIDTH)),
+              _ => Node::Branch(Vec::with_capacity(WIDTH)),
+            };
+            children.push(Rc::new(empty));
+          }
+          node = Rc::make_mut(&mut children[child]);
+        }
+        Node::Leaf(values) => {
+          values.push(value);
+          break;
+        }
+      }
+    }
+    self.len += 1;
+  }
+}
+
+impl<T> Clone for Vector<T> {
+  fn clone(&self) -> Self {
+    Vector {
+      root: self.root.dupe(),
+      shift: self.shift,
+      len: self.len,
+      start: self.start,
+    }
+  }
+}
+impl<T> Dupe for Vector<T> {}
+
+impl<T> Default for Vector<T> {
+  fn default() -> Self {
+    Vector::new()
+  }
+}
+
+impl<T: Clone> FromIterator<T> for Vector<T> {
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut vector = Vector::new();
+    for value in iter {
+      vector.push_back(value);
+    }
+    vector
+  }
+}
+
+impl<T: PartialEq> PartialEq for Vector<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.len() == other.len() && self.iter().eq(other.iter())
+  }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Vector<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.iter()).finish()
+  }
+}
+
+pub struct Iter<'v, T> {
+  vector: &'v Vector<T>,
+  index: usize,
+}
+
+impl<'v, T> Iterator for Iter<'v, T> {
+  type Item = &'v T;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let value = self.vector.get(self.index)?;
+    self.index += 1;
+    Some(value)
+  }
+}
+
+impl<'v, T> IntoIterator for &'v Vector<T> {
+  type Item = &'v T;
+  type IntoIter = Iter<'v, T>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::rc::Rc;
+
+  use super::{Node, Vector};
+
+  #[test]
+  fn push_and_get() {
+    let vector: Vector<usize> = (0..5_000).collect();
+    assert_eq!(vector.len(), 5_000);
+    assert!((0..5_000).all(|idx| vector.get(idx) == Some(&idx)));
+    assert_eq!(vector.get(5_000), None);
+    assert!(vector.iter().copied().eq(0..5_000));
+  }
+
+  #[test]
+  fn push_shares_structure() {
+    let base: Vector<usize> = (0..100).collect();
+    let mut pushed = base.clone();
+    pushed.push_back(100);
+    let mut other = base.clone();
+    other.push_back(7);
+
+    assert_eq!(base.len(), 100);
+    assert_eq!(pushed.get(100), Some(&100));
+    assert_eq!(other.get(100), Some(&7));
+    // only the last leaf and the root were copied
+    let (base_leaf, pushed_leaf) = (root_child(&base, 0), root_child(&pushed, 0));
+    assert!(Rc::ptr_eq(&base_leaf, &pushed_leaf));
+    assert!(!Rc::ptr_eq(&base.root, &pushed.root));
+  }
+
+  fn root_child(vector: &Vector<usize>, child: usize) -> Rc<Node<usize>> {
+    match &*vector.root {
+      Node::Branch(children) => children[child].clone(),
+      Node::Leaf(_) => panic!("root of a single leaf"),
+    }
+  }
+
+  #[test]
+  fn rest() {
+    let vector: Vector<i64> = (1..=3).collect();
+    let rest = vector.rest().unwrap();
+    assert_eq!(rest, (2..=3).collect());
+    assert_eq!(rest.first(), Some(&2));
+
+    let mut pushed = rest.rest().unwrap();
+    pushed.push_back(4);
+    assert_eq!(pushed, [3, 4].into_iter().collect());
+    assert_eq!(vector, (1..=3).collect());
+
+    let empty = pushed.rest().unwrap().rest().unwrap();
+    assert!(empty.is_empty());
+    assert!(empty.rest().is_none());
+  }
+}
@@ -0,0 +1,9 @@
+mod builtins;
+mod environment;
+mod evaluator;
+mod object;
+
+pub use builtins::*;
+pub use environment::*;
+pub use evaluator::*;
+pub use object::*;
@@ -0,0 +1,82 @@
+use std::fmt;
+
+use dupe::Dupe;
+
+use crate::interner::Symbol;
+
+use super::object::Object;
+
+/// Functions available in every program, unless a binding shadows them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Dupe)]
+pub enum Builtin {
+  /// `push(array, value)` or `push(hash, key, value)`
+  Push,
+  /// `rest(array)`: every element but the first one, or `null` if empty
+  Rest,
+}
+
+thread_local! {
+  /// Builtins by their names, interned once per thread
+  static BUILTINS: [(Symbol, Builtin); 2] =
+    [Builtin::Push, Builtin::Rest].map(|builtin| (Symbol::intern(builtin.name()), builtin));
+}
+
+impl Builtin {
+  pub fn lookup(name: Symbol) -> Option<Builtin> {
+    BUILTINS.with(|builtins| {
+      builtins
+        .iter()
+        .find(|(symbol, _)| *symbol == name)
+        .map(|(_, builtin)| *builtin)
+    })
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Builtin::Push => "push",
+      Builtin::Rest => "rest",
+    }
+  }
+
+  pub fn apply(self, args: Vec<Object>) -> Object {
+    match (self, args.as_slice()) {
+      (Builtin::Push, [Object::Array(elements), value]) => {
+        let mut elements = elements.dupe();
+        elements.push_back(value.dupe());
+        Object::Array(elements)
+      }
+      (Builtin::Push, [Object::Hash(pairs), key, value]) => {
+        let Some(key) = key.hash_key() else {
+          return Object::error(format!("unusable as hash key: {}", key.type_name()));
+        };
+        let mut pairs = pairs.dupe();
+        pairs.insert(key, value.dupe());
+        Object::Hash(pairs)
+      }
+      (Builtin::Rest, [Object::Array(elements)]) => match elements.rest() {
+        Some(rest) => Object::Array(rest),
+        None => Object::Null,
+      },
+      (Builtin::Push, [Object::Array(_), ..]) => wrong_arguments(2, args.len()),
+      (Builtin::Push, [Object::Hash(_), ..]) => wrong_arguments(3, args.len()),
+      (Builtin::Rest, [_]) | (Builtin::Push, [_, ..]) => Object::error(format!(
+        "argument to `{self}` not supported, got {}",
+        args[0].type_name()
+      )),
+      (Builtin::Rest, _) => wrong_arguments(1, args.len()),
+      (Builtin::Push, []) => wrong_arguments(2, 0),
+    }
+  }
+}
+
+fn wrong_arguments(expected: usize, got: usize) -> Object {
+  Object::error(format!(
+    "wrong number of arguments: expected {expected}, got {got}"
+  ))
+}
+
+impl fmt::Display for Builtin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
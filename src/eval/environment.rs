@@ -0,0 +1,82 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use dupe::Dupe;
+
+use crate::interner::Symbol;
+
+use super::object::Object;
+
+/// Chain of lexical scopes. Cloning an `Environment` shares the same scope,
+/// which is how closures keep their defining scope alive.
+#[derive(Debug, Default, Clone, Dupe)]
+pub struct Environment {
+  scope: Rc<Scope>,
+}
+
+#[derive(Debug, Default)]
+struct Scope {
+  store: RefCell<HashMap<Symbol, Object>>,
+  outer: Option<Environment>,
+}
+
+impl Environment {
+  pub fn new() -> Environment {
+    Environment::default()
+  }
+
+  /// Creates an empty scope whose lookups fall back to `outer`
+  pub fn enclosed(outer: &Environment) -> Environment {
+    let scope = Scope {
+      store: RefCell::default(),
+      outer: Some(outer.dupe()),
+    };
+    Environment {
+      scope: Rc::new(scope),
+    }
+  }
+
+  pub fn get(&self, name: Symbol) -> Option<Object> {
+    if let Some(value) = self.scope.store.borrow().get(&name) {
+      return Some(value.dupe());
+    }
+    self.scope.outer.as_ref()?.get(name)
+  }
+
+  /// Binds `name` in the innermost scope, shadowing any outer binding
+  pub fn set(&self, name: Symbol, value: Object) {
+    self.scope.store.borrow_mut().insert(name, value);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{eval::Object, interner::Symbol};
+
+  use super::Environment;
+
+  #[test]
+  fn lookup_through_outer_scopes() {
+    let global = Environment::new();
+    global.set(Symbol::intern("a"), Object::Int(1));
+    global.set(Symbol::intern("b"), Object::Int(2));
+
+    let local = Environment::enclosed(&global);
+    local.set(Symbol::intern("b"), Object::Int(3));
+
+    assert_eq!(local.get(Symbol::intern("a")), Some(Object::Int(1)));
+    assert_eq!(local.get(Symbol::intern("b")), Some(Object::Int(3)));
+    assert_eq!(global.get(Symbol::intern("b")), Some(Object::Int(2)));
+    assert_eq!(local.get(Symbol::intern("c")), None);
+  }
+
+  #[test]
+  fn shared_scope() {
+    let env = Environment::new();
+    let closure_env = env.clone();
+    env.set(Symbol::intern("late"), Object::Bool(true));
+    assert_eq!(
+      closure_env.get(Symbol::intern("late")),
+      Some(Object::Bool(true))
+    );
+  }
+}
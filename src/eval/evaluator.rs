@@ -0,0 +1,949 @@
+use std::rc::Rc;
+
+use dupe::{Dupe, OptionDupedExt};
+
+use crate::{
+  ast::{
+    ArrayLiteral, AstNode, Block, Bool, Call, Expression, ExpressionStatement, Float, Func,
+    HashLiteral, Ident, If, Index, Infix, Int, LetStatement, Prefix, Program, ReturnStatement,
+    Statement, StringLiteral, TemplatePart, TemplateString,
+  },
+  collections::{HashMap, Vector},
+  token::TokenKind,
+};
+
+use super::{
+  builtins::Builtin,
+  environment::Environment,
+  object::{Function, Object},
+};
+
+/// Tree-walking evaluator for LPP programs. Bindings made by `let` statements
+/// at the top level persist across calls to [`Evaluator::eval`].
+#[derive(Debug, Default)]
+pub struct Evaluator {
+  env: Environment,
+}
+
+impl Evaluator {
+  pub fn new() -> Evaluator {
+    Evaluator::default()
+  }
+
+  pub fn with_env(env: Environment) -> Evaluator {
+    Evaluator { env }
+  }
+
+  pub fn env(&self) -> &Environment {
+    &self.env
+  }
+
+  /// Evaluates `program`, whose tokens point into `source`, and returns the
+  /// value of the last statement executed.
+  pub fn eval(&self, program: &Program, source: &str) -> Object {
+    let ctx = Context {
+      source: source.into(),
+      env: self.env.dupe(),
+    };
+    program.evaluate(&ctx)
+  }
+}
+
+struct Context {
+  source: Rc<str>,
+  env: Environment,
+}
+
+trait Evaluable {
+  fn evaluate(&self, ctx: &Context) -> Object;
+}
+
+/// Returns early from the enclosing `evaluate` when a sub-expression yields
+/// an error or a return value, since both stop the evaluation of the
+/// expression that contains it.
+macro_rules! propagate {
+  ($obj:expr) => {{
+    let obj = $obj;
+    if matches!(obj, Object::Error(_) | Object::Return(_)) {
+      return obj;
+    }
+    obj
+  }};
+}
+
+impl Evaluable for Program {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let mut result = Object::Null;
+    for st in self.statements() {
+      result = st.evaluate(ctx);
+      match result {
+        Object::Return(value) => return value.as_ref().dupe(),
+        Object::Error(_) => return result,
+        _ => {}
+      }
+    }
+    result
+  }
+}
+
+impl Evaluable for Block {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let mut result = Object::Null;
+    for st in self.statements() {
+      result = st.evaluate(ctx);
+      // the return value is unwrapped by the enclosing program or function
+      if matches!(result, Object::Return(_) | Object::Error(_)) {
+        return result;
+      }
+    }
+    result
+  }
+}
+
+impl Evaluable for Statement {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    match self {
+      Statement::Let(st) => st.evaluate(ctx),
+      Statement::Return(st) => st.evaluate(ctx),
+      Statement::Expression(st) => st.evaluate(ctx),
+      Statement::Block(st) => st.evaluate(ctx),
+    }
+  }
+}
+
+impl Evaluable for LetStatement {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let (name, value) = self.parts();
+    let value = propagate!(value.evaluate(ctx));
+    ctx.env.set(name.symbol(), value);
+    Object::Null
+  }
+}
+
+impl Evaluable for ReturnStatement {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let value = propagate!(self.return_exp().evaluate(ctx));
+    Object::Return(Rc::new(value))
+  }
+}
+
+impl Evaluable for ExpressionStatement {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    self.expression().evaluate(ctx)
+  }
+}
+
+impl Evaluable for Expression {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    match self {
+      Expression::Ident(exp) => exp.evaluate(ctx),
+      Expression::Int(exp) => exp.evaluate(ctx),
+      Expression::Float(exp) => exp.evaluate(ctx),
+      Expression::Prefix(exp) => exp.evaluate(ctx),
+      Expression::Infix(exp) => exp.evaluate(ctx),
+      Expression::Bool(exp) => exp.evaluate(ctx),
+      Expression::If(exp) => exp.evaluate(ctx),
+      Expression::Func(exp) => exp.evaluate(ctx),
+      Expression::Call(exp) => exp.evaluate(ctx),
+      Expression::StringLiteral(exp) => exp.evaluate(ctx),
+      Expression::TemplateString(exp) => exp.evaluate(ctx),
+      Expression::Array(exp) => exp.evaluate(ctx),
+      Expression::Hash(exp) => exp.evaluate(ctx),
+      Expression::Index(exp) => exp.evaluate(ctx),
+    }
+  }
+}
+
+impl Evaluable for Ident {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    if let Some(value) = ctx.env.get(self.symbol()) {
+      return value;
+    }
+    match Builtin::lookup(self.symbol()) {
+      Some(builtin) => Object::Builtin(builtin),
+      None => Object::error(format!("identifier not found: {}", self.symbol())),
+    }
+  }
+}
+
+impl Evaluable for Int {
+  fn evaluate(&self, _ctx: &Context) -> Object {
+    Object::Int(self.value())
+  }
+}
+
+impl Evaluable for Float {
+  fn evaluate(&self, _ctx: &Context) -> Object {
+    Object::Float(self.value())
+  }
+}
+
+impl Evaluable for Bool {
+  fn evaluate(&self, _ctx: &Context) -> Object {
+    Object::Bool(self.value())
+  }
+}
+
+impl Evaluable for StringLiteral {
+  fn evaluate(&self, _ctx: &Context) -> Object {
+    Object::String(self.value())
+  }
+}
+
+impl Evaluable for TemplateString {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let mut result = String::new();
+    for part in self.parts() {
+      match part {
+        TemplatePart::Text(text) => result.push_str(text),
+        TemplatePart::Expression(exp) => {
+          let value = propagate!(exp.evaluate(ctx));
+          result.push_str(&value.to_string());
+        }
+      }
+    }
+    Object::String(result.into())
+  }
+}
+
+impl Evaluable for ArrayLiteral {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let mut elements = Vector::new();
+    for element in self.elements() {
+      elements.push_back(propagate!(element.evaluate(ctx)));
+    }
+    Object::Array(elements)
+  }
+}
+
+impl Evaluable for HashLiteral {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let mut pairs = HashMap::new();
+    for (key, value) in self.pairs() {
+      let key = propagate!(key.evaluate(ctx));
+      let Some(key) = key.hash_key() else {
+        return unusable_hash_key(&key);
+      };
+      let value = propagate!(value.evaluate(ctx));
+      pairs.insert(key, value);
+    }
+    Object::Hash(pairs)
+  }
+}
+
+fn unusable_hash_key(key: &Object) -> Object {
+  Object::error(format!("unusable as hash key: {}", key.type_name()))
+}
+
+impl Evaluable for Index {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let (lhs, index) = self.parts();
+    let lhs = propagate!(lhs.evaluate(ctx));
+    let index = propagate!(index.evaluate(ctx));
+    eval_index(lhs, index)
+  }
+}
+
+fn eval_index(lhs: Object, index: Object) -> Object {
+  match (lhs, index) {
+    (Object::Array(elements), Object::Int(idx)) => {
+      let element = usize::try_from(idx).ok().and_then(|i| elements.get(i));
+      match element {
+        Some(element) => element.dupe(),
+        None => Object::error(format!(
+          "index out of bounds: {idx} (length {})",
+          elements.len()
+        )),
+      }
+    }
+    (Object::Hash(pairs), index) => match index.hash_key() {
+      Some(key) => pairs.get(&key).duped().unwrap_or(Object::Null),
+      None => unusable_hash_key(&index),
+    },
+    (Object::Array(_), index) => Object::error(format!(
+      "array index must be an INTEGER, got {}",
+      index.type_name()
+    )),
+    (lhs, _) => Object::error(format!("index operator not supported: {}", lhs.type_name())),
+  }
+}
+
+impl Evaluable for Prefix {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let (operator, rhs) = self.parts();
+    let rhs = propagate!(rhs.evaluate(ctx));
+    eval_prefix(operator, self.token_literal(&ctx.source), rhs)
+  }
+}
+
+impl Evaluable for Infix {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let (lhs, operator, rhs) = self.parts();
+    let lhs = propagate!(lhs.evaluate(ctx));
+    // `&&` and `||` only evaluate `rhs` when `lhs` doesn't decide the result
+    match (operator, lhs.is_truthy()) {
+      (TokenKind::And, false) => return Object::Bool(false),
+      (TokenKind::Or, true) => return Object::Bool(true),
+      (TokenKind::And | TokenKind::Or, _) => {
+        let rhs = propagate!(rhs.evaluate(ctx));
+        return Object::Bool(rhs.is_truthy());
+      }
+      _ => {}
+    }
+    let rhs = propagate!(rhs.evaluate(ctx));
+    eval_infix(operator, self.token_literal(&ctx.source), lhs, rhs)
+  }
+}
+
+impl Evaluable for If {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let (condition, consequence, alternative) = self.parts();
+    let condition = propagate!(condition.evaluate(ctx));
+
+    if condition.is_truthy() {
+      consequence.evaluate(ctx)
+    } else if let Some(alternative) = alternative {
+      alternative.evaluate(ctx)
+    } else {
+      Object::Null
+    }
+  }
+}
+
+impl Evaluable for Func {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let func = Function::new(
+      self.params(),
+      self.body(),
+      ctx.env.dupe(),
+      ctx.source.dupe(),
+    );
+    Object::Func(Rc::new(func))
+  }
+}
+
+impl Evaluable for Call {
+  fn evaluate(&self, ctx: &Context) -> Object {
+    let (func, args) = self.parts();
+    let func = propagate!(func.evaluate(ctx));
+
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+      values.push(propagate!(arg.evaluate(ctx)));
+    }
+
+    match func {
+      Object::Func(func) => apply_function(&func, values),
+      Object::Builtin(builtin) => builtin.apply(values),
+      other => Object::error(format!("not a function: {}", other.type_name())),
+    }
+  }
+}
+
+fn apply_function(func: &Function, args: Vec<Object>) -> Object {
+  let params = func.params();
+  if params.len() != args.len() {
+    return Object::error(format!(
+      "wrong number of arguments: expected {}, got {}",
+      params.len(),
+      args.len()
+    ));
+  }
+
+  let ctx = Context {
+    source: func.source(),
+    env: Environment::enclosed(func.env()),
+  };
+  for (param, arg) in params.iter().zip(args) {
+    ctx.env.set(param.symbol(), arg);
+  }
+
+  let Some(body) = func.body() else {
+    return Object::Null;
+  };
+  match body.evaluate(&ctx) {
+    Object::Return(value) => value.as_ref().dupe(),
+    result => result,
+  }
+}
+
+fn eval_prefix(operator: TokenKind, literal: &str, rhs: Object) -> Object {
+  match (operator, rhs) {
+    (TokenKind::Neg, rhs) => Object::Bool(!rhs.is_truthy()),
+    (TokenKind::Minus, Object::Int(value)) => match value.checked_neg() {
+      Some(value) => Object::Int(value),
+      None => Object::error(format!("integer overflow: -{value}")),
+    },
+    (TokenKind::Minus, Object::Float(value)) => Object::Float(-value),
+    (_, rhs) => Object::error(format!("unknown operator: {literal}{}", rhs.type_name())),
+  }
+}
+
+fn eval_infix(operator: TokenKind, literal: &str, lhs: Object, rhs: Object) -> Object {
+  match (lhs, rhs) {
+    (Object::Int(lhs), Object::Int(rhs)) => eval_int_infix(operator, literal, lhs, rhs),
+    // integers are promoted when mixed with floats
+    (Object::Float(lhs), Object::Float(rhs)) => eval_float_infix(operator, literal, lhs, rhs),
+    (Object::Int(lhs), Object::Float(rhs)) => eval_float_infix(operator, literal, lhs as f64, rhs),
+    (Object::Float(lhs), Object::Int(rhs)) => eval_float_infix(operator, literal, lhs, rhs as f64),
+    (Object::String(lhs), Object::String(rhs)) => match operator {
+      TokenKind::Plus => Object::String(format!("{lhs}{rhs}").into()),
+      TokenKind::Eq => Object::Bool(lhs == rhs),
+      TokenKind::NotEq => Object::Bool(lhs != rhs),
+      _ => Object::error(format!("unknown operator: STRING {literal} STRING")),
+    },
+    (Object::Bool(lhs), Object::Bool(rhs)) => match operator {
+      TokenKind::Eq => Object::Bool(lhs == rhs),
+      TokenKind::NotEq => Object::Bool(lhs != rhs),
+      _ => Object::error(format!("unknown operator: BOOLEAN {literal} BOOLEAN")),
+    },
+    (lhs, rhs) => {
+      let (lhs, rhs) = (lhs.type_name(), rhs.type_name());
+      if lhs == rhs {
+        Object::error(format!("unknown operator: {lhs} {literal} {rhs}"))
+      } else {
+        Object::error(format!("type mismatch: {lhs} {literal} {rhs}"))
+      }
+    }
+  }
+}
+
+fn eval_int_infix(operator: TokenKind, literal: &str, lhs: i64, rhs: i64) -> Object {
+  let value = match operator {
+    TokenKind::Plus => lhs.checked_add(rhs),
+    TokenKind::Minus => lhs.checked_sub(rhs),
+    TokenKind::Mul => lhs.checked_mul(rhs),
+    TokenKind::Division => {
+      if rhs == 0 {
+        return Object::error("division by zero");
+      }
+      lhs.checked_div(rhs)
+    }
+    TokenKind::Mod => {
+      if rhs == 0 {
+        return Object::error("division by zero");
+      }
+      lhs.checked_rem(rhs)
+    }
+    TokenKind::LT => return Object::Bool(lhs < rhs),
+    TokenKind::GT => return Object::Bool(lhs > rhs),
+    TokenKind::LTEq => return Object::Bool(lhs <= rhs),
+    TokenKind::GTEq => return Object::Bool(lhs >= rhs),
+    TokenKind::Eq => return Object::Bool(lhs == rhs),
+    TokenKind::NotEq => return Object::Bool(lhs != rhs),
+    _ => return Object::error(format!("unknown operator: INTEGER {literal} INTEGER")),
+  };
+  match value {
+    Some(value) => Object::Int(value),
+    None => Object::error(format!("integer overflow: {lhs} {literal} {rhs}")),
+  }
+}
+
+/// IEEE-754 arithmetic, except that dividing by zero is an error as it is for
+/// integers
+fn eval_float_infix(operator: TokenKind, literal: &str, lhs: f64, rhs: f64) -> Object {
+  let value = match operator {
+    TokenKind::Plus => lhs + rhs,
+    TokenKind::Minus => lhs - rhs,
+    TokenKind::Mul => lhs * rhs,
+    TokenKind::Division => {
+      if rhs == 0.0 {
+        return Object::error("division by zero");
+      }
+      lhs / rhs
+    }
+    TokenKind::Mod => {
+      if rhs == 0.0 {
+        return Object::error("division by zero");
+      }
+      lhs % rhs
+    }
+    TokenKind::LT => return Object::Bool(lhs < rhs),
+    TokenKind::GT => return Object::Bool(lhs > rhs),
+    TokenKind::LTEq => return Object::Bool(lhs <= rhs),
+    TokenKind::GTEq => return Object::Bool(lhs >= rhs),
+    TokenKind::Eq => return Object::Bool(lhs == rhs),
+    TokenKind::NotEq => return Object::Bool(lhs != rhs),
+    _ => return Object::error(format!("unknown operator: FLOAT {literal} FLOAT")),
+  };
+  Object::Float(value)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{
+    ast::Program,
+    branch::BranchRoot,
+    eval::Object,
+    lexer::{Keywords, Lexer},
+    parser::Parser,
+    token::TokenKind,
+  };
+
+  use super::{eval_infix, eval_prefix, Evaluator};
+
+  fn eval_source(source: &str) -> Object {
+    eval_source_with(&Evaluator::new(), source)
+  }
+
+  fn eval_source_with(evaluator: &Evaluator, source: &str) -> Object {
+    let lexer = Lexer::new(&source);
+    let mut parser = Parser::new(lexer);
+    let program: Program = parser.branch().inspect().unwrap();
+    evaluator.eval(&program, source)
+  }
+
+  #[test]
+  fn eval_literals() {
+    assert_eq!(eval_source("5;"), Object::Int(5));
+    assert_eq!(eval_source("true;"), Object::Bool(true));
+    assert_eq!(eval_source(r#""hola";"#), Object::String("hola".into()));
+    assert_eq!(eval_source(""), Object::Null);
+  }
+
+  #[test]
+  fn eval_if() {
+    assert_eq!(eval_source("if(true) { 10; };"), Object::Int(10));
+    assert_eq!(eval_source("if(false) { 10; };"), Object::Null);
+    assert_eq!(
+      eval_source("if(false) { 10; } else { 20; };"),
+      Object::Int(20)
+    );
+    assert_eq!(eval_source("if(1) { 10; };"), Object::Int(10));
+  }
+
+  #[test]
+  fn eval_return() {
+    let source = "if(true) { if(true) { return 10; }; return 1; }; 9;";
+    assert_eq!(eval_source(source), Object::Int(10));
+  }
+
+  #[test]
+  fn eval_return_in_subexpression() {
+    let source = "let x = if (true) { return 5; }; 10;";
+    assert_eq!(eval_source(source), Object::Int(5));
+    let source = "1 + if (true) { return 2; };";
+    assert_eq!(eval_source(source), Object::Int(2));
+    let source = "let f = fn() { [1, if (true) { return 3; }]; 4; }; f();";
+    assert_eq!(eval_source(source), Object::Int(3));
+  }
+
+  #[test]
+  fn eval_unknown_identifier() {
+    let result = eval_source("foo;");
+    assert_eq!(result, Object::error("identifier not found: foo"));
+    assert_eq!(result.to_string(), "Error: identifier not found: foo");
+  }
+
+  #[test]
+  fn eval_let_bindings() {
+    assert_eq!(eval_source("let a = 5; a;"), Object::Int(5));
+    assert_eq!(eval_source("let a = 5; let b = a; b;"), Object::Int(5));
+    assert_eq!(eval_source("let a = 5;"), Object::Null);
+  }
+
+  #[test]
+  fn eval_arithmetic() {
+    assert_eq!(
+      eval_source("let a = 5; let b = 10; a + b;"),
+      Object::Int(15)
+    );
+    assert_eq!(
+      eval_source("(5 + 10 * 2 + 15 / 3) * 2 - 10;"),
+      Object::Int(50)
+    );
+    assert_eq!(eval_source("1 < 2 == true;"), Object::Bool(true));
+    assert_eq!(
+      eval_source("0xFF + 0b1_0000 - 10_000_000_000;"),
+      Object::Int(271 - 10_000_000_000)
+    );
+    assert_eq!(
+      eval_source("-9223372036854775807 - 1;"),
+      Object::Int(i64::MIN)
+    );
+  }
+
+  #[test]
+  fn bindings_persist_across_evaluations() {
+    let evaluator = Evaluator::new();
+    eval_source_with(&evaluator, "let a = 5;");
+    assert_eq!(eval_source_with(&evaluator, "a;"), Object::Int(5));
+  }
+
+  #[test]
+  fn apply_function() {
+    assert_eq!(eval_source("fn(y) { y; }(7);"), Object::Int(7));
+    assert_eq!(
+      eval_source("fn(y) { y; }();"),
+      Object::error("wrong number of arguments: expected 1, got 0")
+    );
+  }
+
+  #[test]
+  fn closure_captures_defining_environment() {
+    let evaluator = Evaluator::new();
+    eval_source_with(&evaluator, "let x = 5;");
+    assert_eq!(
+      eval_source_with(&evaluator, "fn() { x; }();"),
+      Object::Int(5)
+    );
+  }
+
+  #[test]
+  fn spanish_dialect() {
+    let source = "
+      variable mayor_de_edad = procedimiento(edad) {
+        si (edad > 17) { regresa verdadero; } si_no { falso }
+      };
+      mayor_de_edad(20);
+    ";
+    let lexer = Lexer::with_keywords(&source, Keywords::spanish());
+    let program = Parser::new(lexer).parse_program();
+    assert_eq!(Evaluator::new().eval(&program, source), Object::Bool(true));
+  }
+
+  #[test]
+  fn unicode_identifiers() {
+    assert_eq!(
+      eval_source("let pingüino = 1; pingüino + 1;"),
+      Object::Int(2)
+    );
+    assert_eq!(
+      eval_source("let caça = 2; let això = caça; això;"),
+      Object::Int(2)
+    );
+
+    // precomposed `é` and `e` + combining acute accent name the same variable
+    let source = "let caf\u{e9} = 5; cafe\u{301} * 2;";
+    assert_eq!(eval_source(source), Object::Int(10));
+    let source = "let f = fn(cafe\u{301}) { caf\u{e9} }; f(3);";
+    assert_eq!(eval_source(source), Object::Int(3));
+  }
+
+  #[test]
+  fn readme_session() {
+    let evaluator = Evaluator::new();
+    let eval = |source: &str| eval_source_with(&evaluator, source);
+
+    eval("let a = 5;");
+    eval("let b = 10;");
+    assert_eq!(eval("a + b;"), Object::Int(15));
+
+    eval(
+      "let mayor_de_edad = fn(edad) {
+        // return edad > 18;
+        if(edad > 18) {
+          return true;
+        } else {
+          return false;
+        }
+      };",
+    );
+    assert_eq!(eval("mayor_de_edad(20);"), Object::Bool(true));
+    assert_eq!(eval("mayor_de_edad(15);"), Object::Bool(false));
+
+    eval(
+      "let sumador = fn(x) {
+        return fn(y) {
+          return x + y;
+        };
+      };",
+    );
+    eval("let suma_dos = sumador(2);");
+    assert_eq!(eval("suma_dos(5);"), Object::Int(7));
+    eval("let suma_cinco = sumador(5);");
+    assert_eq!(eval("suma_cinco(20);"), Object::Int(25));
+    assert_eq!(eval("mayor_de_edad(suma_cinco(20));"), Object::Bool(true));
+  }
+
+  #[test]
+  fn eval_call_errors() {
+    assert_eq!(
+      eval_source("5(1);"),
+      Object::error("not a function: INTEGER")
+    );
+    assert_eq!(
+      eval_source("let f = fn(x) { x; }; f(foo);"),
+      Object::error("identifier not found: foo")
+    );
+  }
+
+  #[test]
+  fn eval_prefix_operators() {
+    assert_eq!(
+      eval_prefix(TokenKind::Neg, "!", Object::Bool(true)),
+      Object::Bool(false)
+    );
+    assert_eq!(
+      eval_prefix(TokenKind::Neg, "!", Object::Int(5)),
+      Object::Bool(false)
+    );
+    assert_eq!(
+      eval_prefix(TokenKind::Minus, "-", Object::Int(5)),
+      Object::Int(-5)
+    );
+    assert_eq!(
+      eval_prefix(TokenKind::Minus, "-", Object::Bool(true)),
+      Object::error("unknown operator: -BOOLEAN")
+    );
+  }
+
+  #[test]
+  fn eval_infix_operators() {
+    let int = Object::Int;
+    assert_eq!(eval_infix(TokenKind::Plus, "+", int(5), int(10)), int(15));
+    assert_eq!(eval_infix(TokenKind::Minus, "-", int(5), int(10)), int(-5));
+    assert_eq!(eval_infix(TokenKind::Mul, "*", int(5), int(10)), int(50));
+    assert_eq!(
+      eval_infix(TokenKind::Division, "/", int(10), int(5)),
+      int(2)
+    );
+    assert_eq!(
+      eval_infix(TokenKind::Division, "/", int(10), int(0)),
+      Object::error("division by zero")
+    );
+    assert_eq!(
+      eval_infix(TokenKind::GT, ">", int(20), int(18)),
+      Object::Bool(true)
+    );
+    assert_eq!(
+      eval_infix(TokenKind::Eq, "==", Object::Bool(true), Object::Bool(false)),
+      Object::Bool(false)
+    );
+    assert_eq!(
+      eval_infix(
+        TokenKind::Plus,
+        "+",
+        Object::String("hola ".into()),
+        Object::String("mundo".into())
+      ),
+      Object::String("hola mundo".into())
+    );
+    assert_eq!(
+      eval_infix(TokenKind::Plus, "+", int(5), Object::Bool(true)),
+      Object::error("type mismatch: INTEGER + BOOLEAN")
+    );
+    assert_eq!(
+      eval_infix(TokenKind::Plus, "+", Object::Bool(true), Object::Bool(true)),
+      Object::error("unknown operator: BOOLEAN + BOOLEAN")
+    );
+  }
+
+  #[test]
+  fn eval_comparison_and_modulo() {
+    assert_eq!(eval_source("7 % 3;"), Object::Int(1));
+    assert_eq!(eval_source("-7 % 3;"), Object::Int(-1));
+    assert_eq!(eval_source("7.5 % 2;"), Object::Float(1.5));
+    assert_eq!(eval_source("1 % 0;"), Object::error("division by zero"));
+    assert_eq!(eval_source("2 <= 2;"), Object::Bool(true));
+    assert_eq!(eval_source("3 <= 2;"), Object::Bool(false));
+    assert_eq!(eval_source("2 >= 2.5;"), Object::Bool(false));
+  }
+
+  #[test]
+  fn eval_logical_operators() {
+    assert_eq!(eval_source("true && false;"), Object::Bool(false));
+    assert_eq!(eval_source("true && 1;"), Object::Bool(true));
+    assert_eq!(eval_source("false || 0;"), Object::Bool(true));
+    assert_eq!(eval_source("1 > 2 || 2 > 1 && 3 > 2;"), Object::Bool(true));
+
+    // the rhs would be an error if it were evaluated
+    assert_eq!(eval_source("false && falla;"), Object::Bool(false));
+    assert_eq!(eval_source("true || 1 / 0;"), Object::Bool(true));
+    assert_eq!(
+      eval_source("true && falla;"),
+      Object::error("identifier not found: falla")
+    );
+  }
+
+  #[test]
+  fn eval_template_strings() {
+    let source = r#"
+      let nombre = "Ana";
+      let edad = 17;
+      "hola {nombre}, tienes {edad + 1} años {edad > 18} \{}";
+    "#;
+    assert_eq!(
+      eval_source(source),
+      Object::String("hola Ana, tienes 18 años false {}".into())
+    );
+    assert_eq!(
+      eval_source(r#""{ "a{1}b" }{2.5}";"#),
+      Object::String("a1b2.5".into())
+    );
+    assert_eq!(
+      eval_source(r#""{falta}";"#),
+      Object::error("identifier not found: falta")
+    );
+    // `{"` is reported as an unescaped brace and kept as text
+    assert_eq!(eval_source(r#""{" + "}";"#), Object::String("{}".into()));
+    assert_eq!(
+      eval_source(r#"let a = "x"; "{" + a + "}";"#),
+      Object::String("{x}".into())
+    );
+  }
+
+  #[test]
+  fn eval_arrays() {
+    let ints = |values: &[i64]| Object::Array(values.iter().map(|v| Object::Int(*v)).collect());
+    assert_eq!(eval_source("[1, 2 * 2, 3 + 3];"), ints(&[1, 4, 6]));
+    assert_eq!(eval_source("[];"), ints(&[]));
+    assert_eq!(eval_source("[1, 2, 3][0];"), Object::Int(1));
+    assert_eq!(eval_source("let i = 1; [1, 2, 3][i + 1];"), Object::Int(3));
+    assert_eq!(
+      eval_source("let a = [1, [2, 3]]; a[1][0] * a[0];"),
+      Object::Int(2)
+    );
+    assert_eq!(
+      eval_source("let f = fn() { [fn(x) { x * 2; }] }; f()[0](4);"),
+      Object::Int(8)
+    );
+    assert_eq!(
+      eval_source(r#"[1, "dos", true];"#).to_string(),
+      r#"[1, "dos", true]"#
+    );
+  }
+
+  #[test]
+  fn eval_index_errors() {
+    assert_eq!(
+      eval_source("[1, 2, 3][3];"),
+      Object::error("index out of bounds: 3 (length 3)")
+    );
+    assert_eq!(
+      eval_source("[1, 2, 3][-1];"),
+      Object::error("index out of bounds: -1 (length 3)")
+    );
+    assert_eq!(
+      eval_source("[1][true];"),
+      Object::error("array index must be an INTEGER, got BOOLEAN")
+    );
+    assert_eq!(
+      eval_source("5[0];"),
+      Object::error("index operator not supported: INTEGER")
+    );
+    assert_eq!(
+      eval_source("[1, falta][0];"),
+      Object::error("identifier not found: falta")
+    );
+  }
+
+  #[test]
+  fn eval_hashes() {
+    let source = r#"
+      let dos = 2;
+      let h = {"uno": 1, dos: "dos", true: [3], "cua" + "tro": 4};
+      [h["uno"], h[2], h[1 < 2][0], h["cuatro"], h["cinco"]];
+    "#;
+    assert_eq!(eval_source(source).to_string(), r#"[1, "dos", 3, 4, null]"#);
+    assert_eq!(eval_source(r#"{"a": 1, "a": 2}["a"];"#), Object::Int(2));
+    assert_eq!(
+      eval_source(r#"{2: "b", 1: "a", "c": true, false: 0};"#).to_string(),
+      r#"{1: "a", 2: "b", false: 0, "c": true}"#
+    );
+    assert_eq!(
+      eval_source("{1: 2} == {1: 2};"),
+      Object::error("unknown operator: HASH == HASH")
+    );
+    assert_eq!(
+      eval_source("{fn(x) { x }: 1};"),
+      Object::error("unusable as hash key: FUNCTION")
+    );
+    assert_eq!(
+      eval_source("{1: 2}[1.5];"),
+      Object::error("unusable as hash key: FLOAT")
+    );
+    assert_eq!(
+      eval_source("{1: falta};"),
+      Object::error("identifier not found: falta")
+    );
+  }
+
+  #[test]
+  fn eval_push_and_rest() {
+    let source = r#"
+      let a = [1, 2];
+      let b = push(a, 3);
+      let c = push(rest(a), 4);
+      [a, b, c, rest([]), rest(rest(a))];
+    "#;
+    assert_eq!(
+      eval_source(source).to_string(),
+      "[[1, 2], [1, 2, 3], [2, 4], null, []]"
+    );
+    let source = r#"
+      let h = {"a": 1};
+      let g = push(push(h, "b", 2), "a", 3);
+      [h, g];
+    "#;
+    assert_eq!(
+      eval_source(source).to_string(),
+      r#"[{"a": 1}, {"a": 3, "b": 2}]"#
+    );
+  }
+
+  #[test]
+  fn eval_builtins_with_closures() {
+    let source = r#"
+      let map = fn(arr, f) {
+        let iter = fn(arr, acc) {
+          // `rest` of an empty array is `null`
+          if (!rest(arr)) { acc } else { iter(rest(arr), push(acc, f(arr[0]))) }
+        };
+        iter(arr, []);
+      };
+      let items = [1, 2, 3];
+      let doubled = map(items, fn(x) { x * 2 });
+      [items, doubled];
+    "#;
+    assert_eq!(eval_source(source).to_string(), "[[1, 2, 3], [2, 4, 6]]");
+    assert_eq!(
+      eval_source("let push = fn(a, b) { a + b }; push(1, 2);"),
+      Object::Int(3)
+    );
+    assert_eq!(eval_source("push;").to_string(), "builtin function push");
+  }
+
+  #[test]
+  fn eval_builtin_errors() {
+    assert_eq!(
+      eval_source("push([1]);"),
+      Object::error("wrong number of arguments: expected 2, got 1")
+    );
+    assert_eq!(
+      eval_source("push({}, 1);"),
+      Object::error("wrong number of arguments: expected 3, got 2")
+    );
+    assert_eq!(
+      eval_source("rest(1);"),
+      Object::error("argument to `rest` not supported, got INTEGER")
+    );
+    assert_eq!(
+      eval_source("push({}, [], 1);"),
+      Object::error("unusable as hash key: ARRAY")
+    );
+  }
+
+  #[test]
+  fn display_strings_in_collections() {
+    let source = r#"[{"x\ny": "v\nw"}, "a, \"b\" \{c\}\u{7}"];"#;
+    let expected = r#"[{"x\ny": "v\nw"}, "a, \"b\" \{c\}\u{7}"]"#;
+    assert_eq!(eval_source(source).to_string(), expected);
+    assert_eq!(eval_source(r#""x\ny";"#).to_string(), "x\ny");
+  }
+
+  #[test]
+  fn eval_float_arithmetic() {
+    let float = Object::Float;
+    assert_eq!(eval_source("2.75;"), float(2.75));
+    assert_eq!(eval_source("1.5 * 2.0 - 0.5;"), float(2.5));
+    assert_eq!(eval_source("-2.5e-1;"), float(-0.25));
+    assert_eq!(eval_source("1 + 0.5;"), float(1.5));
+    assert_eq!(eval_source("7 / 2.0;"), float(3.5));
+    assert_eq!(eval_source("7 / 2;"), Object::Int(3));
+    assert_eq!(eval_source("1 == 1.0;"), Object::Bool(true));
+    assert_eq!(eval_source("0.1 < 1;"), Object::Bool(true));
+    assert_eq!(eval_source("1.0 / 0;"), Object::error("division by zero"));
+    assert_eq!(eval_source("2.0 * 3;").to_string(), "6.0");
+  }
+}
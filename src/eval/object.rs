@@ -0,0 +1,229 @@
+use std::{fmt, rc::Rc};
+
+use dupe::Dupe;
+
+use crate::{
+  ast::{Block, Ident, NodeDisplay},
+  collections::{HashMap, Vector},
+};
+
+use super::{builtins::Builtin, environment::Environment};
+
+/// Runtime values produced by evaluating LPP programs
+#[derive(Debug, Clone, Dupe)]
+pub enum Object {
+  Int(i64),
+  Float(f64),
+  Bool(bool),
+  String(Rc<str>),
+  Array(Vector<Object>),
+  Hash(HashMap<HashKey, Object>),
+  Null,
+  Func(Rc<Function>),
+  Builtin(Builtin),
+  Return(Rc<Object>),
+  Error(Rc<str>),
+}
+
+impl Object {
+  pub fn error(msg: impl Into<Rc<str>>) -> Object {
+    Object::Error(msg.into())
+  }
+
+  pub fn type_name(&self) -> &'static str {
+    match self {
+      Object::Int(_) => "INTEGER",
+      Object::Float(_) => "FLOAT",
+      Object::Bool(_) => "BOOLEAN",
+      Object::String(_) => "STRING",
+      Object::Array(_) => "ARRAY",
+      Object::Hash(_) => "HASH",
+      Object::Null => "NULL",
+      Object::Func(_) => "FUNCTION",
+      Object::Builtin(_) => "BUILTIN",
+      Object::Return(_) => "RETURN",
+      Object::Error(_) => "ERROR",
+    }
+  }
+
+  /// `null` and `false` are falsy, everything else is truthy
+  pub fn is_truthy(&self) -> bool {
+    !matches!(self, Object::Null | Object::Bool(false))
+  }
+
+  pub fn is_error(&self) -> bool {
+    matches!(self, Object::Error(_))
+  }
+
+  /// Key for this value in a hash, if it can be one
+  pub fn hash_key(&self) -> Option<HashKey> {
+    match self {
+      Object::Int(value) => Some(HashKey::Int(*value)),
+      Object::Bool(value) => Some(HashKey::Bool(*value)),
+      Object::String(value) => Some(HashKey::String(value.dupe())),
+      _ => None,
+    }
+  }
+}
+
+/// Values that can be used as hash keys. Floats can't, since `NaN` isn't
+/// equal to itself.
+#[derive(Debug, Clone, Dupe, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HashKey {
+  Int(i64),
+  Bool(bool),
+  String(Rc<str>),
+}
+
+impl From<HashKey> for Object {
+  fn from(key: HashKey) -> Self {
+    match key {
+      HashKey::Int(value) => Object::Int(value),
+      HashKey::Bool(value) => Object::Bool(value),
+      HashKey::String(value) => Object::String(value),
+    }
+  }
+}
+
+impl PartialEq for Object {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Object::Int(a), Object::Int(b)) => a == b,
+      (Object::Float(a), Object::Float(b)) => a == b,
+      (Object::Bool(a), Object::Bool(b)) => a == b,
+      (Object::String(a), Object::String(b)) => a == b,
+      (Object::Array(a), Object::Array(b)) => a == b,
+      (Object::Hash(a), Object::Hash(b)) => a == b,
+      (Object::Null, Object::Null) => true,
+      (Object::Func(a), Object::Func(b)) => Rc::ptr_eq(a, b),
+      (Object::Builtin(a), Object::Builtin(b)) => a == b,
+      (Object::Return(a), Object::Return(b)) => a == b,
+      (Object::Error(a), Object::Error(b)) => a == b,
+      _ => false,
+    }
+  }
+}
+
+impl fmt::Display for Object {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Object::Int(value) => write!(f, "{value}"),
+      Object::Float(value) => write!(f, "{value:?}"),
+      Object::Bool(value) => write!(f, "{value}"),
+      Object::String(value) => write!(f, "{value}"),
+      Object::Array(elements) => {
+        write!(f, "[")?;
+        for (idx, element) in elements.iter().enumerate() {
+          if idx > 0 {
+            write!(f, ", ")?;
+          }
+          write_element(f, element)?;
+        }
+        write!(f, "]")
+      }
+      Object::Hash(pairs) => {
+        // sorted, since a map iterates in hash order
+        let mut pairs: Vec<_> = pairs.iter().collect();
+        pairs.sort_by_key(|&(key, _)| key);
+        write!(f, "{{")?;
+        for (idx, (key, value)) in pairs.into_iter().enumerate() {
+          if idx > 0 {
+            write!(f, ", ")?;
+          }
+          write_element(f, &key.dupe().into())?;
+          write!(f, ": ")?;
+          write_element(f, value)?;
+        }
+        write!(f, "}}")
+      }
+      Object::Null => write!(f, "null"),
+      Object::Func(func) => write!(f, "{func}"),
+      Object::Builtin(builtin) => write!(f, "builtin function {builtin}"),
+      Object::Return(value) => write!(f, "{value}"),
+      Object::Error(msg) => write!(f, "Error: {msg}"),
+    }
+  }
+}
+
+/// Writes a value inside an array or hash. Strings are written as LPP
+/// literals there, so `["a, b"]` isn't mistaken for `["a", "b"]`.
+fn write_element(f: &mut fmt::Formatter<'_>, value: &Object) -> fmt::Result {
+  let Object::String(value) = value else {
+    return write!(f, "{value}");
+  };
+  write!(f, "\"")?;
+  for c in value.chars() {
+    match c {
+      '"' => write!(f, "\\\"")?,
+      '\\' => write!(f, "\\\\")?,
+      '\n' => write!(f, "\\n")?,
+      '\t' => write!(f, "\\t")?,
+      '{' => write!(f, "\\{{")?,
+      '}' => write!(f, "\\}}")?,
+      c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+      c => write!(f, "{c}")?,
+    }
+  }
+  write!(f, "\"")
+}
+
+/// A closure over the environment where the function literal was evaluated.
+/// It keeps the source it was parsed from because the identifiers in its body
+/// are slices of that source.
+pub struct Function {
+  params: Rc<[Ident]>,
+  body: Option<Rc<Block>>,
+  env: Environment,
+  source: Rc<str>,
+}
+
+impl Function {
+  pub fn new(
+    params: Rc<[Ident]>,
+    body: Option<Rc<Block>>,
+    env: Environment,
+    source: Rc<str>,
+  ) -> Function {
+    Function {
+      params,
+      body,
+      env,
+      source,
+    }
+  }
+  pub fn params(&self) -> &[Ident] {
+    &self.params
+  }
+  pub fn body(&self) -> Option<&Block> {
+    self.body.as_deref()
+  }
+  pub fn env(&self) -> &Environment {
+    &self.env
+  }
+  pub fn source(&self) -> Rc<str> {
+    self.source.dupe()
+  }
+}
+
+impl fmt::Debug for Function {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Function({self})")
+  }
+}
+
+impl fmt::Display for Function {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "fn(")?;
+    for (idx, param) in self.params.iter().enumerate() {
+      if idx > 0 {
+        write!(f, ", ")?;
+      }
+      param.source_fmt(&self.source, f)?;
+    }
+    write!(f, ") {{")?;
+    if let Some(body) = &self.body {
+      body.source_fmt(&self.source, f)?;
+    }
+    write!(f, "}}")
+  }
+}
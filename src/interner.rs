@@ -0,0 +1,81 @@
+use std::{cell::RefCell, collections::HashMap, fmt, marker::PhantomData, rc::Rc};
+
+use dupe::Dupe;
+
+use crate::utils::normalize_ident;
+
+thread_local! {
+  static INTERNER: RefCell<Interner> = RefCell::default();
+}
+
+/// Maps identifier names to symbols and back. Names are never freed, so a
+/// symbol stays valid for the whole life of its thread.
+#[derive(Debug, Default)]
+struct Interner {
+  symbols: HashMap<Rc<str>, Symbol>,
+  names: Vec<Rc<str>>,
+}
+
+impl Interner {
+  fn intern(&mut self, name: &str) -> Symbol {
+    if let Some(symbol) = self.symbols.get(name) {
+      return *symbol;
+    }
+    let symbol = Symbol(self.names.len() as u32, PhantomData);
+    let name: Rc<str> = name.into();
+    self.names.push(name.dupe());
+    self.symbols.insert(name, symbol);
+    symbol
+  }
+}
+
+/// Interned identifier name. Symbols are equal when their NFC-normalized
+/// names are, and are only meaningful in the thread that interned them, so
+/// they can't be sent to or shared with other threads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Dupe)]
+pub struct Symbol(u32, PhantomData<Rc<()>>);
+
+impl Symbol {
+  pub fn intern(name: &str) -> Symbol {
+    let name = normalize_ident(name);
+    INTERNER.with(|interner| interner.borrow_mut().intern(&name))
+  }
+
+  /// Normalized name of the symbol
+  pub fn name(self) -> Rc<str> {
+    INTERNER.with(|interner| interner.borrow().names[self.0 as usize].dupe())
+  }
+
+  pub fn as_u32(self) -> u32 {
+    self.0
+  }
+}
+
+impl fmt::Display for Symbol {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Symbol;
+
+  #[test]
+  fn intern() {
+    let a = Symbol::intern("edad");
+    let b = Symbol::intern("nombre");
+    assert_eq!(Symbol::intern("edad"), a);
+    assert_ne!(a, b);
+    assert_eq!(&*a.name(), "edad");
+    assert_eq!(b.to_string(), "nombre");
+  }
+
+  #[test]
+  fn intern_normalized() {
+    let composed = Symbol::intern("caf\u{e9}");
+    let decomposed = Symbol::intern("cafe\u{301}");
+    assert_eq!(composed, decomposed);
+    assert_eq!(&*decomposed.name(), "caf\u{e9}");
+  }
+}
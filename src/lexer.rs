@@ -1,16 +1,33 @@
+mod dispatch;
+mod keywords;
 mod read_from;
 mod source;
+mod source_map;
 
-use crate::token::{Token, TokenKind, TokenValue};
+use crate::token::{Token, TokenKind, TokenValue, Trivia, TriviaKind};
 use crate::types::Literal;
-use read_from::{ExtractStringError, ReadFrom};
+use dispatch::{ascii_ident_len, Dispatch, DISPATCH};
+pub use keywords::{Keyword, Keywords};
+use read_from::{
+  read_string_segment, Comment, ExtractCommentError, ExtractIntError, ExtractStringError, ReadFrom,
+  StringEnd,
+};
 pub use source::Source;
+pub use source_map::{Position, SourceMap, Span};
 
 #[derive(Debug)]
 pub struct Lexer<Source> {
   source: Source,
   pos: usize,
   stop: Option<usize>,
+  error: Option<LexError>,
+  errors: Vec<LexError>,
+  emit_comments: bool,
+  lossless: bool,
+  /// Brace depth inside each open template string interpolation
+  templates: Vec<usize>,
+  keywords: Keywords,
+  source_map: SourceMap,
 }
 
 #[derive(Debug, PartialEq)]
@@ -20,8 +37,62 @@ pub enum LexerStatus {
   ErrorAt(usize),
 }
 
+/// Problem found while lexing. Unterminated strings and comments stop the
+/// lexer, while the rest are reported in [`Lexer::errors`] and lexing goes on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LexError {
+  UnterminatedString,
+  UnterminatedComment,
+  /// Unknown escape sequence in a string literal
+  InvalidEscape(Span),
+  /// Integer literal greater than `i64::MAX`, including the `9223372036854775808`
+  /// in `-9223372036854775808`, whose `-` is a separate operator
+  IntegerOverflow(Span),
+  /// `{}` in a string literal, which interpolates nothing
+  EmptyInterpolation(Span),
+  /// `{` right before the `"` closing a string literal
+  UnclosedInterpolation(Span),
+}
+
+impl LexError {
+  /// Location of the error, for the ones the lexer recovers from
+  pub fn span(&self) -> Option<Span> {
+    match self {
+      LexError::InvalidEscape(span)
+      | LexError::IntegerOverflow(span)
+      | LexError::EmptyInterpolation(span)
+      | LexError::UnclosedInterpolation(span) => Some(*span),
+      LexError::UnterminatedString | LexError::UnterminatedComment => None,
+    }
+  }
+}
+
+impl std::fmt::Display for LexError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      LexError::UnterminatedString => write!(f, "unterminated string"),
+      LexError::UnterminatedComment => write!(f, "unterminated comment"),
+      LexError::InvalidEscape(_) => write!(f, "invalid escape sequence"),
+      LexError::IntegerOverflow(_) => write!(f, "integer literal is too large"),
+      LexError::EmptyInterpolation(_) => {
+        write!(f, "empty interpolation, write `\\{{` for a literal `{{`")
+      }
+      LexError::UnclosedInterpolation(_) => {
+        write!(f, "unclosed interpolation, write `\\{{` for a literal `{{`")
+      }
+    }
+  }
+}
+
 impl<S> Lexer<S> {
+  /// Lexer for the English dialect
   pub fn new(source: &S) -> Lexer<S>
+  where
+    S: Source,
+  {
+    Lexer::with_keywords(source, Keywords::english())
+  }
+  pub fn with_keywords(source: &S, keywords: Keywords) -> Lexer<S>
   where
     S: Source,
   {
@@ -29,8 +100,29 @@ impl<S> Lexer<S> {
       source: source.dupe(),
       pos: 0,
       stop: None,
+      error: None,
+      errors: Vec::new(),
+      emit_comments: false,
+      lossless: false,
+      templates: Vec::new(),
+      keywords,
+      source_map: SourceMap::default(),
     }
   }
+  /// Emits comments as `TokenKind::Comment` tokens instead of skipping them
+  pub fn with_comments(mut self) -> Lexer<S> {
+    self.emit_comments = true;
+    self
+  }
+  /// Attaches whitespace and comments to the tokens as trivia, and ends the
+  /// stream with an empty `TokenKind::EOF` token holding the trivia after the
+  /// last token. Concatenating the full spans of the tokens reproduces the
+  /// source, unless the lexer stops with an error. Comments are never emitted
+  /// as tokens in this mode.
+  pub fn lossless(mut self) -> Lexer<S> {
+    self.lossless = true;
+    self
+  }
   pub fn status(&self) -> LexerStatus {
     match self.stop {
       Some(idx) => {
@@ -49,13 +141,52 @@ impl<S> Lexer<S> {
   {
     self.source.source()
   }
-  fn update_pos(&mut self, len: usize, kind: TokenKind) -> Token {
+  /// Why the lexer stopped, when its status is `LexerStatus::ErrorAt`
+  pub fn error(&self) -> Option<LexError> {
+    self.error
+  }
+  /// Errors the lexer recovered from, in source order
+  pub fn errors(&self) -> &[LexError] {
+    &self.errors
+  }
+  /// Byte offset where the next token will be searched from
+  pub fn offset(&self) -> usize {
+    self.pos
+  }
+  /// Reserved words of the dialect being lexed
+  pub fn keywords(&self) -> &Keywords {
+    &self.keywords
+  }
+  /// Line starts of the text lexed so far
+  pub fn source_map(&self) -> &SourceMap {
+    &self.source_map
+  }
+  /// Line and column of `offset`, which must not be after the lexer position
+  pub fn position(&self, offset: usize) -> Position
+  where
+    S: Source,
+  {
+    self.source_map.position(self.source.source(), offset)
+  }
+  fn update_pos(&mut self, len: usize, kind: TokenKind) -> Token
+  where
+    S: Source,
+  {
     let start = self.pos;
-    self.pos += len;
+    self.advance(len);
     Token::new(kind, start, self.pos)
   }
-  fn mark_blocked(&mut self, stop_len: usize) {
+  fn advance(&mut self, len: usize)
+  where
+    S: Source,
+  {
+    let text = &self.source.source()[self.pos..self.pos + len];
+    self.source_map.record(self.pos, text);
+    self.pos += len;
+  }
+  fn mark_blocked(&mut self, stop_len: usize, error: LexError) {
     self.stop = Some(self.pos + stop_len);
+    self.error = Some(error);
   }
 }
 
@@ -66,79 +197,146 @@ impl<S: Source> Iterator for Lexer<S> {
     if self.stop.is_some() {
       return None;
     }
+    if self.lossless {
+      return self.next_lossless();
+    }
 
-    self.skip_whitespaces();
-    let rem = self.rem();
-    if rem.is_empty() {
+    loop {
+      self.skip_whitespaces();
+      if !self.rem().starts_with('/') {
+        break;
+      }
+      match Comment::read_from(self.rem()) {
+        Some((len, Ok(_))) if self.emit_comments => {
+          return Some((self.update_pos(len, TokenKind::Comment), None));
+        }
+        Some((len, Ok(_))) => self.advance(len),
+        Some((len, Err(ExtractCommentError::Incomplete))) => {
+          self.mark_blocked(len, LexError::UnterminatedComment);
+          return None;
+        }
+        None => break,
+      }
+    }
+    if self.rem().is_empty() {
       self.stop = Some(self.source().len());
       return None;
     }
+    self.lex_token()
+  }
+}
 
-    let mut token_value: Option<TokenValue> = None;
+impl<S: Source> Lexer<S> {
+  fn next_lossless(&mut self) -> Option<(Token, Option<TokenValue>)> {
+    let leading = self.read_trivia(false)?;
+    if self.rem().is_empty() {
+      self.stop = Some(self.pos);
+      let eof = Token::new(TokenKind::EOF, self.pos, self.pos);
+      return Some((eof.with_trivia(leading, Vec::new()), None));
+    }
 
-    let token = if let Some(len) = self.read_char('=') {
-      if let Some(len1) = self.read_char_with_offset('=', len) {
-        self.update_pos(len + len1, TokenKind::Eq)
-      } else {
-        self.update_pos(len, TokenKind::Assign)
-      }
-    } else if let Some(len) = self.read_char('+') {
-      self.update_pos(len, TokenKind::Plus)
-    } else if let Some(len) = self.read_char('\0') {
-      self.update_pos(len, TokenKind::EOF)
-    } else if let Some(len) = self.read_char('(') {
-      self.update_pos(len, TokenKind::LParen)
-    } else if let Some(len) = self.read_char(')') {
-      self.update_pos(len, TokenKind::RParen)
-    } else if let Some(len) = self.read_char('{') {
-      self.update_pos(len, TokenKind::LBrace)
-    } else if let Some(len) = self.read_char('}') {
-      self.update_pos(len, TokenKind::RBrace)
-    } else if let Some(len) = self.read_char(',') {
-      self.update_pos(len, TokenKind::Comma)
-    } else if let Some(len) = self.read_char(';') {
-      self.update_pos(len, TokenKind::Semicolon)
-    } else if let Some(len) = self.read_char('-') {
-      self.update_pos(len, TokenKind::Minus)
-    } else if let Some(len) = self.read_char('/') {
-      self.update_pos(len, TokenKind::Division)
-    } else if let Some(len) = self.read_char('*') {
-      self.update_pos(len, TokenKind::Mul)
-    } else if let Some(len) = self.read_char('<') {
-      self.update_pos(len, TokenKind::LT)
-    } else if let Some(len) = self.read_char('>') {
-      self.update_pos(len, TokenKind::GT)
-    } else if let Some(len) = self.read_char('!') {
-      if let Some(len1) = self.read_char_with_offset('=', len) {
-        self.update_pos(len + len1, TokenKind::NotEq)
-      } else {
-        self.update_pos(len, TokenKind::Neg)
-      }
-    } else if let Some((len, lit)) = Literal::read_from(rem) {
-      let kind = TokenKind::from_literal(lit);
-      self.update_pos(len, kind)
-    } else if let Some((len, value)) = u32::read_from(rem) {
-      token_value = Some(value.into());
-      self.update_pos(len, TokenKind::Int)
-    } else if let Some((len, value)) = String::read_from(rem) {
-      match value {
-        Ok(value) => {
-          token_value = Some(value.into());
-          self.update_pos(len, TokenKind::String)
+    let (token, value) = self.lex_token()?;
+    let trailing = self.read_trivia(true);
+    Some((
+      token.with_trivia(leading, trailing.unwrap_or_default()),
+      value,
+    ))
+  }
+
+  /// Whitespace and comments from the current position. With `same_line`
+  /// it stops before any line break and leaves unterminated comments for the
+  /// next token, otherwise they block the lexer.
+  fn read_trivia(&mut self, same_line: bool) -> Option<Vec<Trivia>> {
+    let mut trivia = Vec::new();
+    loop {
+      let rem = self.rem();
+      let (kind, len) = match rem.bytes().next() {
+        Some(b'\n' | b'\r') if same_line => break,
+        Some(b) if DISPATCH[b as usize] == Dispatch::Whitespace => {
+          let len = rem
+            .bytes()
+            .take_while(|b| match b {
+              b' ' | b'\t' => true,
+              b'\n' | b'\r' => !same_line,
+              _ => false,
+            })
+            .count();
+          (TriviaKind::Whitespace, len)
         }
-        Err(err) => match err {
-          ExtractStringError::Incomplete => {
-            self.mark_blocked(len);
+        _ => match Comment::read_from(rem) {
+          Some((len, Ok(_))) => (TriviaKind::Comment, len),
+          Some(_) if same_line => break,
+          Some((len, Err(ExtractCommentError::Incomplete))) => {
+            self.mark_blocked(len, LexError::UnterminatedComment);
             return None;
           }
+          None => break,
         },
+      };
+      let span = Span::new(self.pos, self.pos + len);
+      trivia.push(Trivia { kind, span });
+      self.advance(len);
+    }
+    Some(trivia)
+  }
+
+  /// Lexes the token at the current position, which must not be at the end
+  fn lex_token(&mut self) -> Option<(Token, Option<TokenValue>)> {
+    let first = self.rem().as_bytes()[0];
+    // braces inside an interpolation are counted to find the `}` closing it
+    if let Some(depth) = self.templates.last_mut() {
+      match first {
+        b'{' => *depth += 1,
+        b'}' if *depth == 0 => {
+          self.templates.pop();
+          return self.lex_template_continuation();
+        }
+        b'}' => *depth -= 1,
+        _ => {}
       }
-    } else {
-      let len = rem.chars().next().unwrap().len_utf8();
-      self.update_pos(len, TokenKind::Illegal)
+    }
+    let token = match DISPATCH[first as usize] {
+      Dispatch::Single(kind) => self.update_pos(1, kind),
+      Dispatch::Pair {
+        next,
+        single,
+        double,
+      } => {
+        if self.rem().as_bytes().get(1) == Some(&next) {
+          self.update_pos(2, double)
+        } else {
+          self.update_pos(1, single)
+        }
+      }
+      // comments were skipped above
+      Dispatch::Slash => self.update_pos(1, TokenKind::Division),
+      Dispatch::Ident => self.lex_word(),
+      Dispatch::NonAscii => self.lex_word(),
+      Dispatch::Digit => return Some(self.lex_number()),
+      Dispatch::Quote => return self.lex_string(),
+      Dispatch::Whitespace | Dispatch::Illegal => self.update_pos(1, TokenKind::Illegal),
     };
-    Some((token, token_value))
+    Some((token, None))
+  }
+}
+
+/// Whether `source` ends inside a string literal or with unclosed `{` or `(`,
+/// meaning that more input is needed before it can be parsed.
+pub fn is_incomplete_input(source: &str) -> bool {
+  let mut lexer = Lexer::new(&source);
+  let mut depth: isize = 0;
+  for (token, _) in lexer.by_ref() {
+    match token.kind() {
+      TokenKind::LBrace | TokenKind::LParen | TokenKind::LBracket | TokenKind::TemplateStart => {
+        depth += 1
+      }
+      TokenKind::RBrace | TokenKind::RParen | TokenKind::RBracket | TokenKind::TemplateEnd => {
+        depth -= 1
+      }
+      _ => {}
+    }
   }
+  depth > 0 || matches!(lexer.status(), LexerStatus::ErrorAt(_))
 }
 
 impl<S: Source> Lexer<S> {
@@ -146,46 +344,364 @@ impl<S: Source> Lexer<S> {
     &self.source()[self.pos..]
   }
 
-  fn read_char(&self, target: char) -> Option<usize> {
-    let mut chars = self.rem().chars();
+  /// Identifier or keyword. ASCII words are sliced directly and only words
+  /// with other chars go through the Unicode tables.
+  fn lex_word(&mut self) -> Token {
+    let rem = self.rem();
+    let ascii_len = ascii_ident_len(rem.as_bytes());
+    let len = match rem.as_bytes().get(ascii_len) {
+      Some(b) if !b.is_ascii() => Literal::read_from(rem).map(|(len, _)| len),
+      _ if ascii_len > 0 => Some(ascii_len),
+      _ => None,
+    };
+    let Some(len) = len else {
+      let len = rem.chars().next().unwrap().len_utf8();
+      return self.update_pos(len, TokenKind::Illegal);
+    };
+    let kind = self.keywords.token_kind(&rem[..len]);
+    self.update_pos(len, kind)
+  }
 
-    match chars.next() {
-      Some(c) if c == target => Some(target.len_utf8()),
-      Some(_) => None,
-      None => None,
+  fn lex_number(&mut self) -> (Token, Option<TokenValue>) {
+    let rem = self.rem();
+    if let Some((len, value)) = f64::read_from(rem) {
+      return (self.update_pos(len, TokenKind::Float), Some(value.into()));
     }
+
+    let (len, value) = i64::read_from(rem).expect("numbers start with a digit");
+    let value = value.unwrap_or_else(|ExtractIntError::Overflow| {
+      let span = Span::new(self.pos, self.pos + len);
+      self.errors.push(LexError::IntegerOverflow(span));
+      0
+    });
+    (self.update_pos(len, TokenKind::Int), Some(value.into()))
   }
 
-  fn read_char_with_offset(&self, target: char, offset: usize) -> Option<usize> {
-    let rem = &self.rem()[offset..];
-    let mut chars = rem.chars();
+  fn lex_string(&mut self) -> Option<(Token, Option<TokenValue>)> {
+    let (len, value) = String::read_from(self.rem()).expect("strings start with a quote");
+    self.lex_string_segment(len, value, TokenKind::String, TokenKind::TemplateStart)
+  }
 
-    match chars.next() {
-      Some(c) if c == target => Some(target.len_utf8()),
-      Some(_) => None,
-      None => None,
-    }
+  /// Rest of a template string after the `}` closing an interpolation
+  fn lex_template_continuation(&mut self) -> Option<(Token, Option<TokenValue>)> {
+    let (len, value) = read_string_segment(self.rem(), 1);
+    self.lex_string_segment(
+      len,
+      value,
+      TokenKind::TemplateEnd,
+      TokenKind::TemplateMiddle,
+    )
   }
 
-  fn skip_whitespaces(&mut self) {
-    let mut n: usize = 0;
-    let mut chars = self.rem().chars();
-    while let Some(c) = chars.next() {
-      if " \t\n\r".contains(c) {
-        n += c.len_utf8();
-      } else {
-        break;
+  /// Emits a string segment of `len` bytes as `closed` when it ends the
+  /// literal, or as `open` when an interpolation follows it
+  fn lex_string_segment(
+    &mut self,
+    len: usize,
+    value: Result<(String, StringEnd), ExtractStringError>,
+    closed: TokenKind,
+    open: TokenKind,
+  ) -> Option<(Token, Option<TokenValue>)> {
+    let (value, end) = match value {
+      Ok(value) => value,
+      Err(ExtractStringError::Incomplete) => {
+        self.mark_blocked(len, LexError::UnterminatedString);
+        return None;
       }
-    }
-    self.pos += n;
+      Err(ExtractStringError::InvalidEscape {
+        offset,
+        len: escape_len,
+        value,
+        end,
+      }) => {
+        let start = self.pos + offset;
+        let span = Span::new(start, start + escape_len);
+        self.errors.push(LexError::InvalidEscape(span));
+        (value, end)
+      }
+      Err(ExtractStringError::EmptyInterpolation { offset, value, end }) => {
+        let start = self.pos + offset;
+        let span = Span::new(start, start + 2);
+        self.errors.push(LexError::EmptyInterpolation(span));
+        (value, end)
+      }
+      Err(ExtractStringError::UnclosedInterpolation { offset, value, end }) => {
+        let start = self.pos + offset;
+        let span = Span::new(start, start + 1);
+        self.errors.push(LexError::UnclosedInterpolation(span));
+        (value, end)
+      }
+    };
+    let kind = match end {
+      StringEnd::Quote => closed,
+      StringEnd::Interpolation => {
+        self.templates.push(0);
+        open
+      }
+    };
+    Some((self.update_pos(len, kind), Some(value.into())))
+  }
+
+  fn skip_whitespaces(&mut self) {
+    let n = self
+      .rem()
+      .bytes()
+      .take_while(|b| DISPATCH[*b as usize] == Dispatch::Whitespace)
+      .count();
+    self.advance(n);
   }
 }
 
 #[cfg(test)]
 mod test {
-  use crate::lexer::LexerStatus;
+  use crate::lexer::{LexError, LexerStatus, Position, Span};
+  use crate::token::{TokenKind, Trivia, TriviaKind};
+
+  use super::{is_incomplete_input, Keywords, Lexer};
+
+  #[test]
+  fn comments() {
+    let source = "a // b\n/* c /* d */ e */ f /* g";
+    let kinds = |lexer: Lexer<&str>| {
+      lexer
+        .map(|(token, _)| (token.kind(), token.literal(source)))
+        .collect::<Vec<_>>()
+    };
+
+    let skipped = kinds(Lexer::new(&source));
+    assert_eq!(skipped, [(TokenKind::Ident, "a"), (TokenKind::Ident, "f")]);
+
+    let emitted = kinds(Lexer::new(&source).with_comments());
+    assert_eq!(
+      emitted,
+      [
+        (TokenKind::Ident, "a"),
+        (TokenKind::Comment, "// b"),
+        (TokenKind::Comment, "/* c /* d */ e */"),
+        (TokenKind::Ident, "f"),
+      ]
+    );
+
+    let mut lexer = Lexer::new(&source);
+    lexer.by_ref().for_each(drop);
+    assert_eq!(lexer.status(), LexerStatus::ErrorAt(source.len()));
+    assert_eq!(lexer.error(), Some(LexError::UnterminatedComment));
+    assert!(is_incomplete_input(source));
+  }
+
+  #[test]
+  fn invalid_escape_does_not_stop() {
+    let source = r#"let s = "a\qb"; s;"#;
+    let mut lexer = Lexer::new(&source);
+    let tokens: Vec<_> = lexer.by_ref().collect();
+
+    assert_eq!(tokens.len(), 7);
+    let (string, value) = &tokens[3];
+    assert_eq!(string.kind(), TokenKind::String);
+    assert_eq!(value.clone().unwrap().to_string().as_deref(), Some(r"a\qb"));
+
+    assert_eq!(lexer.status(), LexerStatus::Ended);
+    assert_eq!(lexer.errors(), [LexError::InvalidEscape(Span::new(10, 12))]);
+  }
+
+  #[test]
+  fn integer_literals() {
+    let source = "0xFF + 0b11 - 1_000 * 99999999999999999999 + 2.5e1;";
+    let mut lexer = Lexer::new(&source);
+    let values: Vec<_> = lexer.by_ref().filter_map(|(_, value)| value).collect();
+
+    let ints: Vec<_> = values.iter().filter_map(|v| v.clone().to_int()).collect();
+    assert_eq!(ints, [255, 3, 1000, 0]);
+    assert_eq!(values[4].clone().to_float(), Some(25.0));
+    assert_eq!(lexer.status(), LexerStatus::Ended);
+    let overflow = Span::new(22, 42);
+    assert_eq!(lexer.errors(), [LexError::IntegerOverflow(overflow)]);
+  }
+
+  #[test]
+  fn spanish_keywords() {
+    let source =
+      "variable f = procedimiento(x) { si (x) { regresa verdadero; } si_no { falso } }; let";
+    let lexer = Lexer::with_keywords(&source, Keywords::spanish());
+    let kinds: Vec<_> = lexer.map(|(token, _)| token.kind()).collect();
 
-  use super::Lexer;
+    use TokenKind::*;
+    let expected = [
+      Let, Ident, Assign, Func, LParen, Ident, RParen, LBrace, If, LParen, Ident, RParen, LBrace,
+      Return, True, Semicolon, RBrace, Else, LBrace, False, RBrace, RBrace, Semicolon, Ident,
+    ];
+    assert_eq!(kinds, expected);
+  }
+
+  #[test]
+  fn comparison_and_logical_operators() {
+    let source = "a <= b >= c < d > e && f || g % h & |";
+    let lexer = Lexer::new(&source);
+    let kinds: Vec<_> = lexer.map(|(token, _)| token.kind()).collect();
+
+    use TokenKind::*;
+    let expected = [
+      Ident, LTEq, Ident, GTEq, Ident, LT, Ident, GT, Ident, And, Ident, Or, Ident, Mod, Ident,
+      Illegal, Illegal,
+    ];
+    assert_eq!(kinds, expected);
+  }
+
+  #[test]
+  fn brackets_and_colons() {
+    let source = "[1, a][0] {b: 2}";
+    let lexer = Lexer::new(&source);
+    let kinds: Vec<_> = lexer.map(|(token, _)| token.kind()).collect();
+
+    use TokenKind::*;
+    let expected = [
+      LBracket, Int, Comma, Ident, RBracket, LBracket, Int, RBracket, LBrace, Ident, Colon, Int,
+      RBrace,
+    ];
+    assert_eq!(kinds, expected);
+  }
+
+  fn full_text(source: &str, lexer: &mut Lexer<&str>) -> String {
+    let spans = lexer.map(|(token, _)| token.full_span());
+    spans.map(|span| &source[span.range()]).collect()
+  }
+
+  #[test]
+  fn lossless_round_trip() {
+    let sources = [
+      include_str!("../fixtures/tokens/tokens.lpp"),
+      include_str!("../fixtures/bench/program.lpp"),
+      "  let a = 1; // uno\r\n\t/* dos */ a /* tres\n */\n\n",
+      "// solo comentarios\n",
+      "",
+    ];
+    for source in sources {
+      let mut lexer = Lexer::new(&source).lossless();
+      assert_eq!(full_text(source, lexer.by_ref()), source);
+      assert_eq!(lexer.status(), LexerStatus::Ended);
+    }
+  }
+
+  #[test]
+  fn lossless_trivia() {
+    let source = "let a = 1; // uno\n  /* dos */ a\n";
+    let tokens: Vec<_> = Lexer::new(&source)
+      .lossless()
+      .map(|(token, _)| token)
+      .collect();
+    let text = |trivia: &[Trivia]| -> Vec<_> {
+      let text = trivia.iter().map(|t| (t.kind, &source[t.span.range()]));
+      text.collect()
+    };
+
+    let semicolon = &tokens[4];
+    assert_eq!(semicolon.kind(), TokenKind::Semicolon);
+    assert_eq!(
+      text(semicolon.trailing_trivia()),
+      [
+        (TriviaKind::Whitespace, " "),
+        (TriviaKind::Comment, "// uno")
+      ]
+    );
+
+    let a = &tokens[5];
+    assert_eq!(
+      text(a.leading_trivia()),
+      [
+        (TriviaKind::Whitespace, "\n  "),
+        (TriviaKind::Comment, "/* dos */"),
+        (TriviaKind::Whitespace, " "),
+      ]
+    );
+    assert_eq!(a.trailing_trivia(), []);
+
+    let eof = &tokens[6];
+    assert_eq!(eof.kind(), TokenKind::EOF);
+    assert_eq!(eof.span(), Span::new(source.len(), source.len()));
+    assert_eq!(text(eof.leading_trivia()), [(TriviaKind::Whitespace, "\n")]);
+  }
+
+  #[test]
+  fn lossless_unterminated_comment() {
+    let source = "a /* b";
+    let mut lexer = Lexer::new(&source).lossless();
+    let (a, _) = lexer.next().unwrap();
+    assert_eq!(a.trailing_trivia().len(), 1);
+    assert!(lexer.next().is_none());
+    assert_eq!(lexer.error(), Some(LexError::UnterminatedComment));
+  }
+
+  #[test]
+  fn template_strings() {
+    let source = r#""a {f({}) + 1} b { "c {d}" }" "e""#;
+    let tokens: Vec<_> = Lexer::new(&source)
+      .map(|(token, value)| {
+        let value = value.and_then(|value| value.to_string());
+        (token.kind(), token.literal(source), value)
+      })
+      .collect();
+
+    use TokenKind::*;
+    let text = |text: &str| Some(text.into());
+    let expected = [
+      (TemplateStart, r#""a {"#, text("a ")),
+      (Ident, "f", None),
+      (LParen, "(", None),
+      (LBrace, "{", None),
+      (RBrace, "}", None),
+      (RParen, ")", None),
+      (Plus, "+", None),
+      (Int, "1", None),
+      (TemplateMiddle, "} b {", text(" b ")),
+      (TemplateStart, r#""c {"#, text("c ")),
+      (Ident, "d", None),
+      (TemplateEnd, r#"}""#, text("")),
+      (TemplateEnd, r#"}""#, text("")),
+      (String, r#""e""#, text("e")),
+    ];
+    assert_eq!(tokens, expected);
+    assert!(is_incomplete_input(r#"let s = "hola {nombre"#));
+  }
+
+  #[test]
+  fn token_positions() {
+    let source = "let año = 5;\n  año + \"a\nb\" + 1;";
+    let mut lexer = Lexer::new(&source);
+    let tokens: Vec<_> = lexer.by_ref().map(|(token, _)| token).collect();
+
+    let positions: Vec<_> = tokens
+      .iter()
+      .map(|token| lexer.position(token.span().start))
+      .map(|Position { line, column }| (line, column))
+      .collect();
+    assert_eq!(
+      positions,
+      [
+        (1, 1),
+        (1, 5),
+        (1, 9),
+        (1, 11),
+        (1, 12),
+        (2, 3),
+        (2, 7),
+        (2, 9),
+        (3, 4),
+        (3, 6),
+        (3, 7)
+      ]
+    );
+  }
+
+  #[test]
+  fn incomplete_input() {
+    assert!(!is_incomplete_input("let a = 5;"));
+    assert!(!is_incomplete_input("fn(x) { x; }"));
+    assert!(is_incomplete_input("let f = fn(x) {"));
+    assert!(is_incomplete_input("f(1,"));
+    assert!(is_incomplete_input("let a = [1,"));
+    assert!(is_incomplete_input(r#"let s = "hello"#));
+    assert!(!is_incomplete_input("}"));
+  }
 
   #[test]
   fn parse_file() {
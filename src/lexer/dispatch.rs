@@ -0,0 +1,129 @@
+use crate::token::TokenKind;
+
+/// What the lexer does when a token starts with a given byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Dispatch {
+  /// Can't start a token
+  Illegal,
+  Whitespace,
+  /// Token made of this byte alone
+  Single(TokenKind),
+  /// `single`, or `double` when the byte is followed by `next`
+  Pair {
+    next: u8,
+    single: TokenKind,
+    double: TokenKind,
+  },
+  /// `/`, which also starts comments
+  Slash,
+  Digit,
+  Quote,
+  /// ASCII letter or `_`
+  Ident,
+  /// First byte of a multibyte char, which may start an identifier
+  NonAscii,
+}
+
+/// Indexed by the first byte of the remaining source
+pub(super) static DISPATCH: [Dispatch; 256] = build_table();
+
+const fn build_table() -> [Dispatch; 256] {
+  use Dispatch::*;
+
+  let mut table = [Illegal; 256];
+  let mut byte = 0;
+  while byte < 256 {
+    let b = byte as u8;
+    table[byte] = match b {
+      b' ' | b'\t' | b'\n' | b'\r' => Whitespace,
+      b'0'..=b'9' => Digit,
+      b'a'..=b'z' | b'A'..=b'Z' | b'_' => Ident,
+      0x80..=0xff => NonAscii,
+      _ => Illegal,
+    };
+    byte += 1;
+  }
+
+  let singles = [
+    (b'+', TokenKind::Plus),
+    (b'-', TokenKind::Minus),
+    (b'*', TokenKind::Mul),
+    (b'%', TokenKind::Mod),
+    (b'(', TokenKind::LParen),
+    (b')', TokenKind::RParen),
+    (b'{', TokenKind::LBrace),
+    (b'}', TokenKind::RBrace),
+    (b'[', TokenKind::LBracket),
+    (b']', TokenKind::RBracket),
+    (b',', TokenKind::Comma),
+    (b':', TokenKind::Colon),
+    (b';', TokenKind::Semicolon),
+    (b'\0', TokenKind::EOF),
+  ];
+  let mut idx = 0;
+  while idx < singles.len() {
+    let (b, kind) = singles[idx];
+    table[b as usize] = Single(kind);
+    idx += 1;
+  }
+
+  // a single `&` or `|` isn't an operator
+  let pairs = [
+    (b'=', b'=', TokenKind::Assign, TokenKind::Eq),
+    (b'!', b'=', TokenKind::Neg, TokenKind::NotEq),
+    (b'<', b'=', TokenKind::LT, TokenKind::LTEq),
+    (b'>', b'=', TokenKind::GT, TokenKind::GTEq),
+    (b'&', b'&', TokenKind::Illegal, TokenKind::And),
+    (b'|', b'|', TokenKind::Illegal, TokenKind::Or),
+  ];
+  let mut idx = 0;
+  while idx < pairs.len() {
+    let (b, next, single, double) = pairs[idx];
+    table[b as usize] = Pair {
+      next,
+      single,
+      double,
+    };
+    idx += 1;
+  }
+
+  table[b'/' as usize] = Slash;
+  table[b'"' as usize] = Quote;
+  table
+}
+
+/// Length of the ASCII identifier chars at the start of `bytes`
+pub(super) fn ascii_ident_len(bytes: &[u8]) -> usize {
+  bytes
+    .iter()
+    .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+    .count()
+}
+
+#[cfg(test)]
+mod test {
+  use crate::token::TokenKind;
+
+  use super::{Dispatch, DISPATCH};
+
+  #[test]
+  fn table() {
+    assert_eq!(DISPATCH[b' ' as usize], Dispatch::Whitespace);
+    assert_eq!(DISPATCH[b'7' as usize], Dispatch::Digit);
+    assert_eq!(DISPATCH[b'x' as usize], Dispatch::Ident);
+    assert_eq!(
+      DISPATCH[b';' as usize],
+      Dispatch::Single(TokenKind::Semicolon)
+    );
+    assert_eq!(DISPATCH[b'@' as usize], Dispatch::Illegal);
+    assert_eq!(DISPATCH["ñ".as_bytes()[0] as usize], Dispatch::NonAscii);
+    assert_eq!(
+      DISPATCH[b'<' as usize],
+      Dispatch::Pair {
+        next: b'=',
+        single: TokenKind::LT,
+        double: TokenKind::LTEq
+      }
+    );
+  }
+}
@@ -0,0 +1,166 @@
+use std::rc::Rc;
+
+use dupe::Dupe;
+
+use crate::token::TokenKind;
+
+/// Language constructs that are spelled with a reserved word
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Dupe)]
+pub enum Keyword {
+  Let,
+  Func,
+  If,
+  Else,
+  Return,
+  True,
+  False,
+}
+
+impl Keyword {
+  fn token_kind(self) -> TokenKind {
+    match self {
+      Keyword::Let => TokenKind::Let,
+      Keyword::Func => TokenKind::Func,
+      Keyword::If => TokenKind::If,
+      Keyword::Else => TokenKind::Else,
+      Keyword::Return => TokenKind::Return,
+      Keyword::True => TokenKind::True,
+      Keyword::False => TokenKind::False,
+    }
+  }
+
+  /// Keyword spelling tokens of `kind`, if they are spelled with one
+  pub fn of_token_kind(kind: TokenKind) -> Option<Keyword> {
+    let keyword = match kind {
+      TokenKind::Let => Keyword::Let,
+      TokenKind::Func => Keyword::Func,
+      TokenKind::If => Keyword::If,
+      TokenKind::Else => Keyword::Else,
+      TokenKind::Return => Keyword::Return,
+      TokenKind::True => Keyword::True,
+      TokenKind::False => Keyword::False,
+      _ => return None,
+    };
+    Some(keyword)
+  }
+}
+
+static ENGLISH: [(&str, Keyword); 7] = [
+  ("else", Keyword::Else),
+  ("false", Keyword::False),
+  ("fn", Keyword::Func),
+  ("if", Keyword::If),
+  ("let", Keyword::Let),
+  ("return", Keyword::Return),
+  ("true", Keyword::True),
+];
+
+/// Keywords of the original LPP from Platzi
+static SPANISH: [(&str, Keyword); 7] = [
+  ("falso", Keyword::False),
+  ("procedimiento", Keyword::Func),
+  ("regresa", Keyword::Return),
+  ("si", Keyword::If),
+  ("si_no", Keyword::Else),
+  ("variable", Keyword::Let),
+  ("verdadero", Keyword::True),
+];
+
+/// Table of reserved words used by a [`Lexer`](super::Lexer). Words missing
+/// from the table are lexed as identifiers. Cloning it is cheap.
+#[derive(Clone, Debug, PartialEq, Eq, Dupe)]
+pub struct Keywords {
+  /// Sorted by word
+  table: Rc<[(Box<str>, Keyword)]>,
+}
+
+impl Default for Keywords {
+  fn default() -> Self {
+    Keywords::english()
+  }
+}
+
+impl Keywords {
+  /// `let`, `fn`, `if`, `else`, `return`, `true` and `false`
+  pub fn english() -> Keywords {
+    Keywords::new(ENGLISH)
+  }
+
+  /// `variable`, `procedimiento`, `si`, `si_no`, `regresa`, `verdadero` and
+  /// `falso`
+  pub fn spanish() -> Keywords {
+    Keywords::new(SPANISH)
+  }
+
+  /// Custom table. When a word appears more than once, the last one wins.
+  pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, Keyword)>) -> Keywords {
+    let mut table: Vec<(Box<str>, Keyword)> = Vec::new();
+    for (word, keyword) in words {
+      match table.binary_search_by(|(text, _)| text.as_ref().cmp(word)) {
+        Ok(idx) => table[idx].1 = keyword,
+        Err(idx) => table.insert(idx, (word.into(), keyword)),
+      }
+    }
+    Keywords {
+      table: table.into(),
+    }
+  }
+
+  pub fn get(&self, word: &str) -> Option<Keyword> {
+    let idx = self
+      .table
+      .binary_search_by(|(text, _)| text.as_ref().cmp(word))
+      .ok()?;
+    Some(self.table[idx].1)
+  }
+
+  /// Word that spells `keyword`. When there are several, the first one in
+  /// alphabetical order is returned.
+  pub fn word(&self, keyword: Keyword) -> Option<&str> {
+    self
+      .table
+      .iter()
+      .find(|(_, kw)| *kw == keyword)
+      .map(|(word, _)| word.as_ref())
+  }
+
+  /// Kind of the token spelled `word`, which is an identifier unless it is a
+  /// keyword
+  pub(crate) fn token_kind(&self, word: &str) -> TokenKind {
+    match self.get(word) {
+      Some(keyword) => keyword.token_kind(),
+      None => TokenKind::Ident,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Keyword, Keywords};
+
+  #[test]
+  fn dialects() {
+    let english = Keywords::english();
+    assert_eq!(english.get("let"), Some(Keyword::Let));
+    assert_eq!(english.get("variable"), None);
+
+    let spanish = Keywords::spanish();
+    assert_eq!(spanish.get("si_no"), Some(Keyword::Else));
+    assert_eq!(spanish.get("si"), Some(Keyword::If));
+    assert_eq!(spanish.get("if"), None);
+    assert_eq!(spanish.word(Keyword::Else), Some("si_no"));
+    assert_eq!(english.word(Keyword::Func), Some("fn"));
+  }
+
+  #[test]
+  fn custom_table() {
+    let keywords = Keywords::new([
+      ("var", Keyword::Let),
+      ("fun", Keyword::Func),
+      ("var", Keyword::If),
+    ]);
+    assert_eq!(keywords.get("var"), Some(Keyword::If));
+    assert_eq!(keywords.get("fun"), Some(Keyword::Func));
+    assert_eq!(keywords.get("let"), None);
+  }
+}
@@ -14,83 +14,367 @@ impl<'s> ReadFrom<'s> for Literal<'s> {
   }
 }
 
-impl<'s> ReadFrom<'s> for u32 {
-  type Value = u32;
+impl<'s> ReadFrom<'s> for i64 {
+  type Value = Result<i64, ExtractIntError>;
 
+  /// Decimal, hex (`0x`) or binary (`0b`) digits, optionally separated by `_`.
+  /// Literals have no sign, since `-` is a prefix operator, so the largest
+  /// one is `i64::MAX` and `i64::MIN` has to be written as
+  /// `-9223372036854775807 - 1`.
   fn read_from(text: &'s str) -> Option<(usize, Self::Value)> {
-    let mut value = 0;
-    let mut len = 0;
-    let chars = text.chars().take_while(|c| is_digit(*c));
-    for c in chars {
-      let d = c as u32 - '0' as u32;
-      value = 10 * value + d;
-      len += c.len_utf8();
+    if !text.starts_with(is_digit) {
+      return None;
     }
 
-    if len == 0 {
-      None
-    } else {
-      Some((len, value))
+    // `0x` without digits is just a `0` followed by an identifier
+    let has_digits = |radix| text[2..].starts_with(|c: char| c.is_digit(radix));
+    let (prefix_len, radix) = match text.get(..2) {
+      Some("0x" | "0X") if has_digits(16) => (2, 16),
+      Some("0b" | "0B") if has_digits(2) => (2, 2),
+      _ => (0, 10),
+    };
+    let in_literal = |c: &char| *c == '_' || c.is_digit(radix);
+
+    // digits, `_` and the prefix are ASCII, so chars and bytes are the same
+    let len = prefix_len + text[prefix_len..].chars().take_while(in_literal).count();
+    let mut value: Option<i64> = Some(0);
+    for c in text[prefix_len..len].chars().filter(|c| *c != '_') {
+      let d = c.to_digit(radix).unwrap() as i64;
+      value = value
+        .and_then(|v| v.checked_mul(radix as i64))
+        .and_then(|v| v.checked_add(d));
+    }
+
+    match value {
+      Some(value) => Some((len, Ok(value))),
+      None => Some((len, Err(ExtractIntError::Overflow))),
     }
   }
 }
 
+impl<'s> ReadFrom<'s> for f64 {
+  type Value = f64;
+
+  /// Decimal digits with a fraction (`3.14`), an exponent (`1e-3`) or both.
+  /// Digits can be separated by `_`. Plain integers aren't read.
+  fn read_from(text: &'s str) -> Option<(usize, f64)> {
+    let digits = |from: usize| {
+      let in_literal = |c: &char| *c == '_' || c.is_ascii_digit();
+      from + text[from..].chars().take_while(in_literal).count()
+    };
+    let starts_with_digit = |from: usize| text[from..].starts_with(|c: char| c.is_ascii_digit());
+
+    if !starts_with_digit(0) {
+      return None;
+    }
+    // all the chars read are ASCII, so chars and bytes are the same
+    let mut len = digits(0);
+    let mut is_float = false;
+
+    // `1.` isn't a float, the `.` needs a digit after it
+    if text[len..].starts_with('.') && starts_with_digit(len + 1) {
+      len = digits(len + 1);
+      is_float = true;
+    }
+
+    if text[len..].starts_with(['e', 'E']) {
+      let sign = usize::from(text[len + 1..].starts_with(['+', '-']));
+      if starts_with_digit(len + 1 + sign) {
+        len = digits(len + 1 + sign);
+        is_float = true;
+      }
+    }
+
+    if !is_float {
+      return None;
+    }
+    let value = text[..len].replace('_', "").parse().ok()?;
+    Some((len, value))
+  }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExtractIntError {
+  /// The literal doesn't fit in an `i64`
+  Overflow,
+}
+
 impl<'s> ReadFrom<'s> for String {
-  type Value = Result<String, ExtractStringError>;
+  type Value = Result<(String, StringEnd), ExtractStringError>;
 
-  // missing support for scaping characters
+  /// Supported escape sequences are `\n`, `\t`, `\\`, `\"`, `\{`, `\}` and
+  /// `\u{XXXX}`. An unescaped `{` starts an interpolated expression and ends
+  /// the first segment of the literal, unless `}` or the closing `"` follows
+  /// it right away, which is reported as a brace missing its `\`.
   fn read_from(text: &'s str) -> Option<(usize, Self::Value)> {
-    let mut len = 0;
-    let mut chars = text.chars();
-
-    match chars.next() {
-      Some('"') => len += 1,
-      None => return None,
-      Some(_) => return None,
+    if !text.starts_with('"') {
+      return None;
     }
+    Some(read_string_segment(text, 1))
+  }
+}
 
-    let mut value = String::new();
+/// How a segment of a string literal ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringEnd {
+  /// `"`, closing the literal
+  Quote,
+  /// `{`, starting an interpolated expression
+  Interpolation,
+}
+
+/// Reads a string literal segment from `start`, which is right after the `"`
+/// or the `}` that closes an interpolated expression. The returned length
+/// includes the `start` bytes.
+pub fn read_string_segment(
+  text: &str,
+  start: usize,
+) -> (usize, Result<(String, StringEnd), ExtractStringError>) {
+  let mut len = start;
+  let mut value = String::new();
+  // first problem found, which doesn't stop reading the segment
+  let mut problem = None;
 
-    loop {
-      match chars.next() {
-        Some('"') => {
-          len += 1;
-          break;
+  let end = loop {
+    let rem = &text[len..];
+    let Some(c) = rem.chars().next() else {
+      return (len, Err(ExtractStringError::Incomplete));
+    };
+    match c {
+      '"' => {
+        len += 1;
+        break StringEnd::Quote;
+      }
+      // `{}` and `{"` are most likely meant as text, so they're kept as such.
+      // A string nested right after the `{` needs a space before it.
+      '{' if rem[1..].starts_with('}') => {
+        problem.get_or_insert(StringProblem::EmptyInterpolation(len));
+        value.push_str("{}");
+        len += 2;
+      }
+      '{' if rem[1..].starts_with('"') => {
+        problem.get_or_insert(StringProblem::UnclosedInterpolation(len));
+        value.push('{');
+        len += 1;
+      }
+      '{' => {
+        len += 1;
+        break StringEnd::Interpolation;
+      }
+      '\\' => match read_escape(&rem[1..]) {
+        Ok((escape_len, escaped)) => {
+          value.push(escaped);
+          len += 1 + escape_len;
         }
-        None => return Some((len, Err(ExtractStringError::Incomplete))),
-        Some(c) => {
-          len += c.len_utf8();
-          value.push(c);
+        Err(0) => return (text.len(), Err(ExtractStringError::Incomplete)),
+        Err(escape_len) => {
+          // keep the invalid sequence as it was written
+          problem.get_or_insert(StringProblem::InvalidEscape(len, 1 + escape_len));
+          value.push_str(&rem[..1 + escape_len]);
+          len += 1 + escape_len;
         }
+      },
+      c => {
+        value.push(c);
+        len += c.len_utf8();
       }
     }
+  };
 
-    Some((len, Ok(value)))
+  let error = match problem {
+    Some(StringProblem::InvalidEscape(offset, escape_len)) => ExtractStringError::InvalidEscape {
+      offset,
+      len: escape_len,
+      value,
+      end,
+    },
+    Some(StringProblem::EmptyInterpolation(offset)) => {
+      ExtractStringError::EmptyInterpolation { offset, value, end }
+    }
+    Some(StringProblem::UnclosedInterpolation(offset)) => {
+      ExtractStringError::UnclosedInterpolation { offset, value, end }
+    }
+    None => return (len, Ok((value, end))),
+  };
+  (len, Err(error))
+}
+
+/// Offset of a problem in a string segment, along with its length for
+/// escape sequences
+enum StringProblem {
+  InvalidEscape(usize, usize),
+  EmptyInterpolation(usize),
+  UnclosedInterpolation(usize),
+}
+
+/// Reads the escape sequence that follows a `\`, returning its length and the
+/// escaped char. On failure it returns the length of the invalid sequence,
+/// which is 0 when the text ends right after the `\`.
+fn read_escape(text: &str) -> Result<(usize, char), usize> {
+  let escaped = match text.chars().next() {
+    Some('n') => '\n',
+    Some('t') => '\t',
+    Some('\\') => '\\',
+    Some('"') => '"',
+    Some('{') => '{',
+    Some('}') => '}',
+    Some('u') => return read_unicode_escape(text),
+    Some(c) => return Err(c.len_utf8()),
+    None => return Err(0),
+  };
+  Ok((1, escaped))
+}
+
+/// `u{XXXX}` with 1 to 6 hex digits naming a valid char
+fn read_unicode_escape(text: &str) -> Result<(usize, char), usize> {
+  let Some(rem) = text[1..].strip_prefix('{') else {
+    return Err(1);
+  };
+  let digits = rem.bytes().take_while(u8::is_ascii_hexdigit).count();
+  let len = 2 + digits;
+  if !(1..=6).contains(&digits) || !rem[digits..].starts_with('}') {
+    return Err(len);
+  }
+
+  let code = u32::from_str_radix(&rem[..digits], 16).map_err(|_| len)?;
+  match char::from_u32(code) {
+    Some(c) => Ok((len + 1, c)),
+    None => Err(len + 1),
   }
 }
+
 #[derive(Debug)]
 pub enum ExtractStringError {
   Incomplete,
+  /// The escape sequence of `len` bytes at `offset` isn't valid. `value` keeps
+  /// it unescaped so lexing can go on.
+  InvalidEscape {
+    offset: usize,
+    len: usize,
+    value: String,
+    end: StringEnd,
+  },
+  /// The `{` at `offset` is followed by `}`, so there's nothing to
+  /// interpolate. `value` keeps both braces as text.
+  EmptyInterpolation {
+    offset: usize,
+    value: String,
+    end: StringEnd,
+  },
+  /// The `{` at `offset` is followed by the closing `"`, so the interpolation
+  /// is never closed. `value` keeps the brace as text.
+  UnclosedInterpolation {
+    offset: usize,
+    value: String,
+    end: StringEnd,
+  },
+}
+
+/// `// ...` up to the end of the line, or `/* ... */`, which can be nested
+pub struct Comment;
+
+impl<'s> ReadFrom<'s> for Comment {
+  type Value = Result<(), ExtractCommentError>;
+
+  fn read_from(text: &'s str) -> Option<(usize, Self::Value)> {
+    if text.starts_with("//") {
+      let len = text.find('\n').unwrap_or(text.len());
+      return Some((len, Ok(())));
+    }
+    if !text.starts_with("/*") {
+      return None;
+    }
+
+    // `/` and `*` are ASCII, so they can't be part of a multibyte char
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut idx = 0;
+    while idx + 1 < bytes.len() {
+      match &bytes[idx..idx + 2] {
+        b"/*" => {
+          depth += 1;
+          idx += 2;
+        }
+        b"*/" => {
+          depth -= 1;
+          idx += 2;
+          if depth == 0 {
+            return Some((idx, Ok(())));
+          }
+        }
+        _ => idx += 1,
+      }
+    }
+    Some((text.len(), Err(ExtractCommentError::Incomplete)))
+  }
+}
+
+#[derive(Debug)]
+pub enum ExtractCommentError {
+  Incomplete,
 }
 
 #[cfg(test)]
 mod test {
   use crate::types::Literal;
 
-  use super::ReadFrom;
+  use super::{
+    read_string_segment, Comment, ExtractIntError, ExtractStringError, ReadFrom, StringEnd,
+  };
 
   #[test]
-  fn read_u32_ok() {
+  fn read_int_ok() {
     let source = "1234sdf";
-    let (len, val) = u32::read_from(source).unwrap();
+    let (len, val) = i64::read_from(source).unwrap();
     assert_eq!(len, 4);
-    assert_eq!(val, 1234);
+    assert_eq!(val, Ok(1234));
+  }
+
+  #[test]
+  fn read_int_wrong() {
+    assert!(i64::read_from(" 1234").is_none());
+    assert!(i64::read_from("hola1234").is_none());
+    assert!(i64::read_from("_1234").is_none());
+  }
+
+  #[test]
+  fn read_int_radix_and_separators() {
+    let read = |source| i64::read_from(source).unwrap();
+    assert_eq!(read("1_000_000;"), (9, Ok(1_000_000)));
+    assert_eq!(read("0xff_FF "), (7, Ok(0xffff)));
+    assert_eq!(read("0b1010_0101"), (11, Ok(0b1010_0101)));
+    assert_eq!(read("0b102"), (4, Ok(0b10)));
+    assert_eq!(read("0xg"), (1, Ok(0)));
+    assert_eq!(read("0x_1"), (1, Ok(0)));
+  }
+
+  #[test]
+  fn read_int_overflow() {
+    let max = "9_223_372_036_854_775_807";
+    assert_eq!(i64::read_from(max), Some((max.len(), Ok(i64::MAX))));
+    assert_eq!(
+      i64::read_from("99999999999999999999 "),
+      Some((20, Err(ExtractIntError::Overflow)))
+    );
+    assert_eq!(
+      i64::read_from("0x8000_0000_0000_0000"),
+      Some((21, Err(ExtractIntError::Overflow)))
+    );
   }
 
   #[test]
-  fn read_u32_wrong() {
-    assert!(u32::read_from(" 1234").is_none());
-    assert!(u32::read_from("hola1234").is_none());
+  fn read_float() {
+    let read = |source| f64::read_from(source);
+    assert_eq!(read("2.75;"), Some((4, 2.75)));
+    assert_eq!(read("1_000.5"), Some((7, 1000.5)));
+    assert_eq!(read("2e3 "), Some((3, 2000.0)));
+    assert_eq!(read("1.5E-2"), Some((6, 0.015)));
+    assert_eq!(read("6.02e+23"), Some((8, 6.02e23)));
+    assert_eq!(read("1.e5"), None);
+    assert_eq!(read("12"), None);
+    assert_eq!(read("1e"), None);
+    assert_eq!(read("1ex"), None);
+    assert_eq!(read(".5"), None);
   }
 
   #[test]
@@ -106,19 +390,143 @@ mod test {
     assert_eq!(lit.as_ref(), "_12hola");
   }
 
+  #[test]
+  fn read_literal_unicode() {
+    let read = |source| Literal::read_from(source).map(|(len, _)| &source[..len]);
+    assert_eq!(read("pingüino + 1"), Some("pingüino"));
+    assert_eq!(read("caça;"), Some("caça"));
+    assert_eq!(read("això="), Some("això"));
+    assert_eq!(read("кошка("), Some("кошка"));
+    assert_eq!(read("変数 "), Some("変数"));
+    // `e` followed by a combining acute accent
+    assert_eq!(read("cafe\u{301};"), Some("cafe\u{301}"));
+    assert_eq!(read("\u{301}a"), None);
+  }
+
   #[test]
   fn read_literal_wrong() {
     let source = "12hola_ñandu chau";
     assert!(Literal::read_from(source).is_none());
   }
+  #[test]
+  fn read_literal_symbols_are_not_letters() {
+    assert!(Literal::read_from("😀").is_none());
+    assert!(Literal::read_from("·a").is_none());
+  }
 
   #[test]
   fn read_string_ok() {
     let source = r#""Hello World""#;
     let (len, result) = String::read_from(source).unwrap();
     assert_eq!(len, 13);
-    let string = result.unwrap();
+    let (string, end) = result.unwrap();
+    assert_eq!(end, StringEnd::Quote);
     assert_eq!(string, "Hello World");
     assert_eq!(string.len(), 11);
   }
+
+  #[test]
+  fn read_string_escapes() {
+    let source = r#""a\n\tb \\ \"c\" \u{F1}\u{1F600} \{\}" rest"#;
+    let (len, result) = String::read_from(source).unwrap();
+    assert_eq!(&source[len..], " rest");
+    let (string, _) = result.unwrap();
+    assert_eq!(string, "a\n\tb \\ \"c\" ñ😀 {}");
+  }
+
+  #[test]
+  fn read_string_invalid_escape() {
+    let source = r#""ok \q \u{110000} \z" rest"#;
+    let (len, result) = String::read_from(source).unwrap();
+    assert_eq!(&source[len..], " rest");
+    match result {
+      Err(ExtractStringError::InvalidEscape {
+        offset, len, value, ..
+      }) => {
+        assert_eq!(offset, 4);
+        assert_eq!(len, 2);
+        assert_eq!(value, r"ok \q \u{110000} \z");
+      }
+      other => panic!("unexpected {other:?}"),
+    }
+
+    for source in [r#""\u{}""#, r#""\u{1234567}""#, r#""\u12""#] {
+      let (_, result) = String::read_from(source).unwrap();
+      assert!(matches!(
+        result,
+        Err(ExtractStringError::InvalidEscape { offset: 1, .. })
+      ));
+    }
+  }
+
+  #[test]
+  fn read_string_segments() {
+    let source = r#""hola {nombre}, tienes {edad + 1} años" rest"#;
+    let (len, result) = String::read_from(source).unwrap();
+    assert_eq!(&source[..len], r#""hola {"#);
+    assert_eq!(result.unwrap(), ("hola ".into(), StringEnd::Interpolation));
+
+    let rem = &source[source.find('}').unwrap()..];
+    let (len, result) = read_string_segment(rem, 1);
+    assert_eq!(&rem[..len], "}, tienes {");
+    assert_eq!(
+      result.unwrap(),
+      (", tienes ".into(), StringEnd::Interpolation)
+    );
+
+    let rem = &source[source.rfind('}').unwrap()..];
+    let (len, result) = read_string_segment(rem, 1);
+    assert_eq!(&rem[len..], " rest");
+    assert_eq!(result.unwrap(), (" años".into(), StringEnd::Quote));
+  }
+
+  #[test]
+  fn read_string_unescaped_braces() {
+    let source = r#""a {} b {" rest"#;
+    let (len, result) = String::read_from(source).unwrap();
+    assert_eq!(&source[len..], " rest");
+    match result {
+      Err(ExtractStringError::EmptyInterpolation { offset, value, end }) => {
+        assert_eq!(offset, 3);
+        assert_eq!(value, "a {} b {");
+        assert_eq!(end, StringEnd::Quote);
+      }
+      other => panic!("unexpected {other:?}"),
+    }
+
+    let (_, result) = String::read_from(r#""{""#).unwrap();
+    assert!(matches!(
+      result,
+      Err(ExtractStringError::UnclosedInterpolation { offset: 1, .. })
+    ));
+  }
+
+  #[test]
+  fn read_string_incomplete() {
+    for source in [r#""abc"#, r#""abc\"#, r#""abc\""#] {
+      let (len, result) = String::read_from(source).unwrap();
+      assert_eq!(len, source.len());
+      assert!(matches!(result, Err(ExtractStringError::Incomplete)));
+    }
+  }
+
+  #[test]
+  fn read_comment_ok() {
+    let (len, result) = Comment::read_from("// hola\nlet").unwrap();
+    assert_eq!(len, 7);
+    assert!(result.is_ok());
+
+    let source = "/* a /* ñ */ b */ let";
+    let (len, result) = Comment::read_from(source).unwrap();
+    assert_eq!(&source[..len], "/* a /* ñ */ b */");
+    assert!(result.is_ok());
+  }
+
+  #[test]
+  fn read_comment_wrong() {
+    assert!(Comment::read_from("/ 2").is_none());
+    let (len, result) = Comment::read_from("/* a /* b */").unwrap();
+    assert_eq!(len, 12);
+    assert!(result.is_err());
+  }
 }
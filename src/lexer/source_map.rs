@@ -0,0 +1,139 @@
+use std::{fmt, ops::Range};
+
+/// Byte range of a token or node in the source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+}
+
+impl Span {
+  pub fn new(start: usize, end: usize) -> Span {
+    Span { start, end }
+  }
+
+  /// Smallest span covering both `self` and `other`
+  pub fn join(self, other: Span) -> Span {
+    Span {
+      start: self.start.min(other.start),
+      end: self.end.max(other.end),
+    }
+  }
+
+  pub fn range(&self) -> Range<usize> {
+    self.start..self.end
+  }
+}
+
+/// 1-based line and column. Columns count chars, not bytes, so `ñ` takes a
+/// single column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+  pub line: usize,
+  pub column: usize,
+}
+
+impl fmt::Display for Position {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}:{}", self.line, self.column)
+  }
+}
+
+/// Converts byte offsets into [`Position`]s by remembering where every line
+/// starts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMap {
+  line_starts: Vec<usize>,
+}
+
+impl Default for SourceMap {
+  fn default() -> Self {
+    SourceMap {
+      line_starts: vec![0],
+    }
+  }
+}
+
+impl SourceMap {
+  pub fn new(source: &str) -> SourceMap {
+    let mut map = SourceMap::default();
+    map.record(0, source);
+    map
+  }
+
+  /// Registers the line breaks of `text`, which is located at `offset` in the
+  /// source. Text must be recorded in order and only once.
+  pub(crate) fn record(&mut self, offset: usize, text: &str) {
+    let newlines = text.bytes().enumerate().filter(|(_, b)| *b == b'\n');
+    for (idx, _) in newlines {
+      self.line_starts.push(offset + idx + 1);
+    }
+  }
+
+  /// Number of lines recorded so far
+  pub fn lines(&self) -> usize {
+    self.line_starts.len()
+  }
+
+  /// 1-based line containing `offset`
+  pub fn line(&self, offset: usize) -> usize {
+    self.line_starts.partition_point(|start| *start <= offset)
+  }
+
+  /// Byte offset where the 1-based `line` starts
+  pub fn line_start(&self, line: usize) -> Option<usize> {
+    self.line_starts.get(line.checked_sub(1)?).copied()
+  }
+
+  pub fn position(&self, source: &str, offset: usize) -> Position {
+    let line = self.line(offset);
+    let line_start = self.line_starts[line - 1];
+    let column = source[line_start..offset].chars().count() + 1;
+    Position { line, column }
+  }
+
+  /// Positions of the first char of `span` and of the char right after it
+  pub fn span_positions(&self, source: &str, span: Span) -> (Position, Position) {
+    (
+      self.position(source, span.start),
+      self.position(source, span.end),
+    )
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Position, SourceMap, Span};
+
+  #[test]
+  fn positions() {
+    let source = "let a = 5;\nlet ñandú = a;\n\n  ñandú;";
+    let map = SourceMap::new(source);
+    assert_eq!(map.lines(), 4);
+
+    let pos = |line, column| Position { line, column };
+    assert_eq!(map.position(source, 0), pos(1, 1));
+    assert_eq!(map.position(source, 4), pos(1, 5));
+    assert_eq!(map.position(source, 10), pos(1, 11));
+    assert_eq!(map.position(source, 11), pos(2, 1));
+
+    // `=` after the two-byte `ñ` and `ú`
+    let eq = source.find("= a").unwrap();
+    assert_eq!(map.position(source, eq), pos(2, 11));
+
+    let last = source.rfind("ñandú").unwrap();
+    let span = Span::new(last, last + "ñandú".len());
+    assert_eq!(map.span_positions(source, span), (pos(4, 3), pos(4, 8)));
+  }
+
+  #[test]
+  fn incremental_record() {
+    let source = "a\nbb\nccc";
+    let mut map = SourceMap::default();
+    map.record(0, &source[..3]);
+    map.record(3, &source[3..]);
+    assert_eq!(map, SourceMap::new(source));
+    assert_eq!(map.line_start(3), Some(5));
+    assert_eq!(map.line_start(0), None);
+  }
+}
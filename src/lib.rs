@@ -1,11 +1,16 @@
-mod ast;
+pub mod ast;
 mod branch;
 pub mod collections;
+mod eval;
+mod interner;
 mod lexer;
 mod parser;
 mod token;
 mod types;
 mod utils;
 
+pub use eval::*;
+pub use interner::Symbol;
 pub use lexer::*;
 pub use parser::*;
+pub use token::{Token, TokenKind, TokenValue, Trivia, TriviaKind};
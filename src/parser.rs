@@ -1,4 +1,62 @@
+mod error;
 mod parsable;
 mod parser;
 
+pub use error::*;
 pub use parser::*;
+
+use crate::ast::{Expression, Program};
+use crate::lexer::Lexer;
+
+/// Outcome of parsing a whole source: the AST, or every error found in it
+pub type ParseResult<T> = Result<T, Vec<ParseError>>;
+
+/// Parses `source` as a program. The nodes of the resulting AST point into
+/// `source`, so it's also needed to display or evaluate them.
+///
+/// ```
+/// let source = "let a = 5; a + 10;";
+/// let program = lpp_rs::parse(source).unwrap();
+/// assert_eq!(program.statements().len(), 2);
+///
+/// let result = lpp_rs::Evaluator::new().eval(&program, source);
+/// assert_eq!(result.to_string(), "15");
+/// ```
+///
+/// Use [`Parser::parse_program`] to get the statements that could be parsed
+/// along with the errors.
+pub fn parse(source: &str) -> ParseResult<Program> {
+  let mut parser = Parser::new(Lexer::new(&source));
+  let program = parser.parse_program();
+  let errors = parser.errors();
+  if errors.is_empty() {
+    Ok(program)
+  } else {
+    Err(errors)
+  }
+}
+
+/// Parses `source` as a single expression, optionally followed by a `;`.
+///
+/// ```
+/// use lpp_rs::ast::NodeFormatter;
+///
+/// let source = "1 + 2 * 3";
+/// let expression = lpp_rs::parse_expression(source).unwrap();
+/// assert_eq!(NodeFormatter::new(source, &expression).to_string(), "(1 + (2 * 3))");
+///
+/// let errors = lpp_rs::parse_expression("1 + 2 3").err().unwrap();
+/// assert_eq!(
+///   errors[0].to_string(),
+///   "1:7: unexpected integer, expected one of `;`, end of file"
+/// );
+/// ```
+pub fn parse_expression(source: &str) -> ParseResult<Expression> {
+  let mut parser = Parser::new(Lexer::new(&source));
+  let expression = parser.parse_expression();
+  let errors = parser.errors();
+  match expression {
+    Some(expression) if errors.is_empty() => Ok(expression),
+    _ => Err(errors),
+  }
+}
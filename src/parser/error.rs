@@ -0,0 +1,116 @@
+use std::fmt;
+
+use crate::lexer::{Keyword, Keywords, LexError, Position, Span};
+use crate::token::{Token, TokenKind};
+
+use super::MAX_NESTING_DEPTH;
+
+/// Error found while parsing, located at `span`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+  kind: ParseErrorKind,
+  span: Span,
+  position: Position,
+  /// Dialect the keywords in the message are spelled in
+  keywords: Keywords,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+  /// `found` doesn't match any of the `expected` token kinds
+  UnexpectedToken {
+    found: Token,
+    expected: Vec<TokenKind>,
+  },
+  /// The input ended while one of `expected` was still needed
+  UnexpectedEof { expected: Vec<TokenKind> },
+  /// The lexer couldn't read the rest of the source
+  Lex(LexError),
+  /// Expressions or blocks are nested deeper than [`MAX_NESTING_DEPTH`]
+  NestingTooDeep,
+}
+
+impl ParseError {
+  pub fn new(kind: ParseErrorKind, span: Span, position: Position) -> ParseError {
+    ParseError {
+      kind,
+      span,
+      position,
+      keywords: Keywords::default(),
+    }
+  }
+  /// Spells the keywords in the message as `keywords` does
+  pub fn with_keywords(self, keywords: Keywords) -> ParseError {
+    ParseError { keywords, ..self }
+  }
+  pub fn kind(&self) -> &ParseErrorKind {
+    &self.kind
+  }
+  pub fn span(&self) -> Span {
+    self.span
+  }
+  /// Line and column where the error starts
+  pub fn position(&self) -> Position {
+    self.position
+  }
+  /// The offending token, if the error wasn't caused by the end of the input
+  pub fn token(&self) -> Option<&Token> {
+    match &self.kind {
+      ParseErrorKind::UnexpectedToken { found, .. } => Some(found),
+      _ => None,
+    }
+  }
+  /// Token kinds that would have been accepted instead
+  pub fn expected(&self) -> &[TokenKind] {
+    match &self.kind {
+      ParseErrorKind::UnexpectedToken { expected, .. } => expected,
+      ParseErrorKind::UnexpectedEof { expected } => expected,
+      ParseErrorKind::Lex(_) | ParseErrorKind::NestingTooDeep => &[],
+    }
+  }
+  /// `kind` as it appears in messages, with keywords spelled in the dialect
+  /// of the source
+  fn fmt_kind(&self, kind: TokenKind, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let word = Keyword::of_token_kind(kind).and_then(|keyword| self.keywords.word(keyword));
+    match word {
+      Some(word) => write!(f, "`{word}`"),
+      None => write!(f, "{kind}"),
+    }
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: ", self.position)?;
+    match &self.kind {
+      ParseErrorKind::UnexpectedToken { found, .. } => {
+        write!(f, "unexpected ")?;
+        self.fmt_kind(found.kind(), f)?;
+      }
+      ParseErrorKind::UnexpectedEof { .. } => write!(f, "unexpected end of input")?,
+      ParseErrorKind::Lex(error) => return write!(f, "{error}"),
+      ParseErrorKind::NestingTooDeep => {
+        return write!(f, "nesting is deeper than {MAX_NESTING_DEPTH} levels")
+      }
+    }
+
+    match self.expected() {
+      [] => Ok(()),
+      [kind] => {
+        write!(f, ", expected ")?;
+        self.fmt_kind(*kind, f)
+      }
+      [first, rest @ ..] => {
+        write!(f, ", expected one of ")?;
+        self.fmt_kind(*first, f)?;
+        for kind in rest {
+          write!(f, ", ")?;
+          self.fmt_kind(*kind, f)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+impl std::error::Error for ParseError {}
@@ -1,35 +1,147 @@
+use std::rc::Rc;
+
 use crate::{
   ast::{
-    Block, Bool, Expression, ExpressionStatement, Ident, If, Int, LetStatement, Prefix, Program,
-    ReturnStatement, Statement, StringLiteral,
+    ArrayLiteral, Block, Bool, Call, Expression, ExpressionStatement, Float, Func, HashLiteral,
+    Ident, If, Index, Infix, Int, LetStatement, Prefix, Program, ReturnStatement, Statement,
+    StringLiteral, TemplatePart, TemplateString,
   },
-  branch::{Branch, Inspect},
+  branch::{Branch, BranchData, Inspect},
   lexer::Source,
-  token::TokenKind,
+  token::{Token, TokenKind},
 };
 
-use super::parser::{ParseError, Parser};
+use super::{
+  parser::{Parser, Precedence},
+  ParseErrorKind,
+};
 
-trait Parsable: Sized {
+trait Parsable: Sized + Clone + 'static {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self>;
 }
+/// Each node type is parsed at most once per token position, so trying
+/// alternatives that share a prefix doesn't parse it again.
 impl<P: Parsable, S: Source> Inspect<Parser<S>> for P {
   fn inspect(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
-    P::parse(branch)
+    let start = branch.token_pos;
+    if let Some(memo) = branch.root().memoized::<P>(start) {
+      let (node, end) = memo?;
+      branch.update_from(end);
+      return Some(node);
+    }
+
+    let node = P::parse(branch);
+    let entry = node
+      .as_ref()
+      .map(|node| (node.clone(), branch.child_data()));
+    branch.root().memoize(start, entry);
+    node
   }
 }
 
 impl Parsable for Program {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
     let mut statements: Vec<Statement> = Vec::new();
-    while let Some(st) = branch.inspect() {
-      statements.push(st)
+    // once the lexer stops, every later failure reports that same stop
+    let mut lexer_stop_reported = false;
+    loop {
+      let start = branch.token_pos;
+      if let Some(st) = branch.inspect() {
+        statements.push(st);
+        continue;
+      }
+
+      let at_end = branch.peek_next_token().is_none();
+      if !at_end || branch.root().lexer_failed() {
+        let error = branch.root().failure_error();
+        let lexer_stop = matches!(error.kind(), ParseErrorKind::Lex(_));
+        if !(lexer_stop && lexer_stop_reported) {
+          branch.root().add_error(error);
+        }
+        lexer_stop_reported |= lexer_stop;
+      }
+      if at_end {
+        break;
+      }
+      synchronize(branch, start);
     }
 
     Some(Program::new(statements))
   }
 }
 
+/// Skips the statement that failed to parse from `start`, so parsing can
+/// resume after it. The statement ends at the first `;` or block closing `}`
+/// at its top level, or right before a `let` or `return` keyword, as long as
+/// they come after the point where parsing failed. A `{` that is never closed
+/// doesn't count, so a `let` or `return` starting a line after it also ends
+/// the statement.
+fn synchronize<S: Source>(branch: &mut Branch<'_, Parser<S>>, start: usize) {
+  let failure_pos = branch.root().failure_pos();
+  let mut depth = 0usize;
+  let mut unclosed = None;
+
+  while let Some(token) = branch.peek_next_token() {
+    let past_failure = branch.token_pos >= failure_pos;
+    match token.kind() {
+      TokenKind::Let | TokenKind::Return if past_failure && branch.token_pos > start => {
+        if depth == 0 {
+          return;
+        }
+        if starts_line(branch) && *unclosed.get_or_insert_with(|| !closes(branch, depth)) {
+          return;
+        }
+      }
+      TokenKind::Semicolon if depth == 0 && past_failure => {
+        branch.take_next_token();
+        return;
+      }
+      TokenKind::RBrace if depth <= 1 && past_failure => {
+        branch.take_next_token();
+        branch.take_next_token_by_kind(TokenKind::Semicolon);
+        return;
+      }
+      TokenKind::LBrace => depth += 1,
+      TokenKind::RBrace => depth = depth.saturating_sub(1),
+      _ => {}
+    }
+    branch.take_next_token();
+  }
+}
+
+/// Whether the next token is the first one in its line
+fn starts_line<S: Source>(branch: &Branch<'_, Parser<S>>) -> bool {
+  let root = branch.root();
+  let prev = branch
+    .token_pos
+    .checked_sub(1)
+    .and_then(|pos| root.token_at(pos));
+  match (prev, branch.peek_next_token()) {
+    (Some(prev), Some(token)) => {
+      root.position(prev.span().end).line < root.position(token.span().start).line
+    }
+    _ => true,
+  }
+}
+
+/// Whether `depth` open braces get closed from the next token on
+fn closes<S: Source>(branch: &Branch<'_, Parser<S>>, mut depth: usize) -> bool {
+  let mut token_pos = branch.token_pos;
+  while let Some(token) = branch.root().token_at(token_pos) {
+    match token.kind() {
+      TokenKind::LBrace => depth += 1,
+      TokenKind::RBrace if depth == 1 => return true,
+      TokenKind::RBrace => depth -= 1,
+      _ => {}
+    }
+    token_pos += 1;
+  }
+  false
+}
+
+/// A `{` starting a statement opens a block, unless the block doesn't parse
+/// and a hash literal does, like in `{"a": 1}["a"];`. So `{}` is an empty
+/// block there, and an empty hash anywhere else.
 impl Parsable for Statement {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
     if let Some(st) = branch.inspect() {
@@ -39,10 +151,10 @@ impl Parsable for Statement {
       return Some(Statement::Return(st));
     }
     if let Some(st) = branch.inspect() {
-      return Some(Statement::Expression(st));
+      return Some(Statement::Block(st));
     }
     if let Some(st) = branch.inspect() {
-      return Some(Statement::Block(st));
+      return Some(Statement::Expression(st));
     }
     None
   }
@@ -75,7 +187,18 @@ impl Parsable for ReturnStatement {
 impl Parsable for ExpressionStatement {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
     let expression: Expression = branch.inspect()?;
-    branch.take_next_token_by_kind(TokenKind::Semicolon)?;
+    // the semicolon is optional after a block, like in `if(...) {...}`, and
+    // before the end of the enclosing block or of the input
+    if branch
+      .take_next_token_by_kind(TokenKind::Semicolon)
+      .is_none()
+    {
+      let ends_in_block = matches!(expression, Expression::If(_) | Expression::Func(_));
+      let next = branch.peek_next_token().map(|token| token.kind());
+      if !ends_in_block && !matches!(next, None | Some(TokenKind::RBrace)) {
+        return None;
+      }
+    }
     let st = ExpressionStatement::new(expression);
     Some(st)
   }
@@ -85,99 +208,252 @@ impl Parsable for Block {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
     let token = branch.take_next_token_by_kind(TokenKind::LBrace)?;
 
-    let mut statements = Vec::new();
-    loop {
-      if let Some(_) = branch.take_next_token_by_kind(TokenKind::RBrace) {
-        return Some(Block::new(token, statements));
-      }
-      match branch.inspect::<Statement>() {
-        Some(st) => statements.push(st),
-        None => {
-          if !statements.is_empty() {
-            branch
-              .root()
-              .add_error(ParseError::Msg("Expected a statement".into()))
-          }
-          return None;
+    let root = branch.root();
+    root.nested(branch.token_pos, || {
+      let mut statements = Vec::new();
+      loop {
+        if let Some(end) = branch.take_next_token_by_kind(TokenKind::RBrace) {
+          return Some(Block::new(token, statements, end));
         }
+        statements.push(branch.inspect::<Statement>()?);
       }
-    }
+    })
   }
 }
 
 impl Parsable for Expression {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
-    if let Some(ident) = branch.inspect() {
-      return Some(Expression::Ident(ident));
-    }
+    parse_expression(branch, Precedence::Lowest)
+  }
+}
 
-    if let Some(int) = branch.inspect() {
-      return Some(Expression::Int(int));
-    }
+/// Pratt parser: a prefix expression followed by every infix operator that
+/// binds tighter than `precedence`. Operators of the same precedence are left
+/// associative.
+fn parse_expression<S: Source>(
+  branch: &mut Branch<'_, Parser<S>>,
+  precedence: Precedence,
+) -> Option<Expression> {
+  let root = branch.root();
+  root.nested(branch.token_pos, || parse_operations(branch, precedence))
+}
 
-    if let Some(boolean) = branch.inspect() {
-      return Some(Expression::Bool(boolean));
-    }
+fn parse_operations<S: Source>(
+  branch: &mut Branch<'_, Parser<S>>,
+  precedence: Precedence,
+) -> Option<Expression> {
+  let mut lhs = parse_prefix_expression(branch)?;
 
-    if let Some(string_literal) = branch.inspect() {
-      return Some(Expression::StringLiteral(string_literal));
+  while let Some(token) = branch.peek_next_token() {
+    let infix_precedence = Precedence::of_infix(token.kind());
+    if infix_precedence <= precedence {
+      break;
     }
+    lhs = branch.scoped(|b| {
+      let operator_token = b.take_next_token()?;
+      if operator_token.kind() == TokenKind::LParen {
+        let (args, end) = parse_list(b, TokenKind::RParen)?;
+        let call = Call::new(operator_token, lhs, Some(args), end);
+        return Some(Expression::Call(call));
+      }
+      if operator_token.kind() == TokenKind::LBracket {
+        let index: Expression = b.inspect()?;
+        let end = b.take_next_token_by_kind(TokenKind::RBracket)?;
+        let index = Index::new(operator_token, lhs, index, end);
+        return Some(Expression::Index(index));
+      }
+      let operator = b.root().token_literal(&operator_token);
+      let rhs = parse_expression(b, infix_precedence)?;
+      Some(Expression::Infix(Infix::new(
+        operator_token,
+        lhs,
+        operator,
+        rhs,
+      )))
+    })?;
+  }
 
-    if let Some(st) = branch.inspect() {
-      return Some(Expression::If(st));
-    }
+  Some(lhs)
+}
 
-    None
+fn parse_prefix_expression<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Expression> {
+  if let Some(ident) = branch.inspect() {
+    return Some(Expression::Ident(ident));
+  }
+
+  if let Some(int) = branch.inspect() {
+    return Some(Expression::Int(int));
+  }
+
+  if let Some(float) = branch.inspect() {
+    return Some(Expression::Float(float));
+  }
+
+  if let Some(boolean) = branch.inspect() {
+    return Some(Expression::Bool(boolean));
+  }
+
+  if let Some(string_literal) = branch.inspect() {
+    return Some(Expression::StringLiteral(string_literal));
+  }
+
+  if let Some(template) = branch.inspect() {
+    return Some(Expression::TemplateString(template));
+  }
+
+  if let Some(array) = branch.inspect() {
+    return Some(Expression::Array(array));
+  }
+
+  if let Some(hash) = branch.inspect() {
+    return Some(Expression::Hash(hash));
+  }
 
-    // pub enum Expression {
-    //   [x] Ident(Ident),
-    //   [x] Int(Int),
-    //   [x] Bool(Bool),
-    //   [x] StringLiteral(StringLiteral),
-    //   [x] If(If),
-    //   [ ] Func(Func),
-    //   [ ] Call(Call),
-    //   [x] Prefix(Prefix),
-    //   [ ] Infix(Infix),
-    // }
+  if let Some(st) = branch.inspect() {
+    return Some(Expression::If(st));
   }
+
+  if let Some(func) = branch.inspect() {
+    return Some(Expression::Func(func));
+  }
+
+  if let Some(prefix) = branch.inspect() {
+    return Some(Expression::Prefix(prefix));
+  }
+
+  // (exp)
+  if let Some(grouped) = branch.scoped(|b| {
+    b.take_next_token_by_kind(TokenKind::LParen)?;
+    let exp: Expression = b.inspect()?;
+    b.take_next_token_by_kind(TokenKind::RParen)?;
+    Some(exp)
+  }) {
+    return Some(grouped);
+  }
+
+  None
+}
+
+/// item (, item)* close
+///
+/// Returns the items along with the closing token.
+fn parse_list<T: Parsable, S: Source>(
+  branch: &mut Branch<'_, Parser<S>>,
+  close: TokenKind,
+) -> Option<(Vec<T>, Token)> {
+  let mut items = Vec::new();
+  if let Some(end) = branch.take_next_token_by_kind(close) {
+    return Some((items, end));
+  }
+  loop {
+    items.push(branch.inspect()?);
+    if branch.take_next_token_by_kind(TokenKind::Comma).is_none() {
+      break;
+    }
+  }
+  let end = branch.take_next_token_by_kind(close)?;
+  Some((items, end))
 }
 
 impl Parsable for Ident {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
     let ident_token = branch.take_next_token_by_kind(TokenKind::Ident)?;
-    Some(Ident::new(ident_token))
+    let symbol = branch.root().intern(&ident_token);
+    Some(Ident::new(ident_token, symbol))
   }
 }
 
 impl Parsable for Int {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
-    let token = branch.take_next_token_by_kind(TokenKind::Int)?;
-    let value = branch.take_next_value()?.to_int()?;
+    let (token, value) = branch.take_next_literal(TokenKind::Int)?;
+    let value = value.to_int()?;
     Some(Int::new(token, value))
   }
 }
 
+impl Parsable for Float {
+  fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
+    let (token, value) = branch.take_next_literal(TokenKind::Float)?;
+    let value = value.to_float()?;
+    Some(Float::new(token, value))
+  }
+}
+
 impl Parsable for Bool {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
-    let token = branch.take_next_token()?;
-    let value = match token.kind() {
-      TokenKind::True => true,
-      TokenKind::False => false,
-      _ => return None,
-    };
+    let token = branch.take_next_token_of(&[TokenKind::True, TokenKind::False])?;
+    let value = token.kind() == TokenKind::True;
     Some(Bool::new(token, value))
   }
 }
 
 impl Parsable for StringLiteral {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
-    let token = branch.take_next_token_by_kind(TokenKind::String)?;
-    let value = branch.take_next_value()?.to_string()?;
+    let (token, value) = branch.take_next_literal(TokenKind::String)?;
+    let value = value.to_string()?;
     Some(StringLiteral::new(token, value))
   }
 }
 
+impl Parsable for TemplateString {
+  fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
+    let (token, text) = branch.take_next_literal(TokenKind::TemplateStart)?;
+    let mut parts = Vec::new();
+    let mut text = text.to_string()?;
+    loop {
+      if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+      }
+      parts.push(TemplatePart::Expression(branch.inspect()?));
+
+      // only `}` is reported as expected, whichever segment comes next
+      let kind = match branch.peek_next_token().map(|token| token.kind()) {
+        Some(TokenKind::TemplateMiddle) => TokenKind::TemplateMiddle,
+        _ => TokenKind::TemplateEnd,
+      };
+      let (segment, segment_text) = branch.take_next_literal(kind)?;
+      text = segment_text.to_string()?;
+      if kind == TokenKind::TemplateEnd {
+        if !text.is_empty() {
+          parts.push(TemplatePart::Text(text));
+        }
+        return Some(TemplateString::new(token, parts, segment));
+      }
+    }
+  }
+}
+
+/// [exp1, exp2, ...]
+impl Parsable for ArrayLiteral {
+  fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
+    let token = branch.take_next_token_by_kind(TokenKind::LBracket)?;
+    let (elements, end) = parse_list(branch, TokenKind::RBracket)?;
+    Some(ArrayLiteral::new(token, elements, end))
+  }
+}
+
+/// {key1: value1, key2: value2, ...}
+impl Parsable for HashLiteral {
+  fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
+    let token = branch.take_next_token_by_kind(TokenKind::LBrace)?;
+    let mut pairs = Vec::new();
+    if let Some(end) = branch.take_next_token_by_kind(TokenKind::RBrace) {
+      return Some(HashLiteral::new(token, pairs, end));
+    }
+    loop {
+      let key: Expression = branch.inspect()?;
+      branch.take_next_token_by_kind(TokenKind::Colon)?;
+      let value: Expression = branch.inspect()?;
+      pairs.push((key, value));
+      if branch.take_next_token_by_kind(TokenKind::Comma).is_none() {
+        break;
+      }
+    }
+    let end = branch.take_next_token_by_kind(TokenKind::RBrace)?;
+    Some(HashLiteral::new(token, pairs, end))
+  }
+}
+
 impl Parsable for If {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
     let if_token = branch.take_next_token_by_kind(TokenKind::If)?;
@@ -196,21 +472,30 @@ impl Parsable for If {
       if_token,
       condition.into(),
       consequence.into(),
-      alternative.map(Box::new),
+      alternative.map(Rc::new),
     ))
   }
 }
 
+/// fn(param1, param2, ...) { ... }
+impl Parsable for Func {
+  fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
+    let func_token = branch.take_next_token_by_kind(TokenKind::Func)?;
+    let _lparent = branch.take_next_token_by_kind(TokenKind::LParen)?;
+    let (params, _rparent): (Vec<Ident>, _) = parse_list(branch, TokenKind::RParen)?;
+    let body: Block = branch.inspect()?;
+
+    Some(Func::new(func_token, params, Some(body)))
+  }
+}
+
 static _PREFIX_TOKENS: [TokenKind; 2] = [TokenKind::Neg, TokenKind::Minus];
 
 /// (! | -)exp
 impl Parsable for Prefix {
   fn parse<S: Source>(branch: &mut Branch<'_, Parser<S>>) -> Option<Self> {
-    let prefix_token = branch.take_next_token()?;
-    if !_PREFIX_TOKENS.contains(&prefix_token.kind()) {
-      return None;
-    }
-    let expression: Expression = branch.inspect()?;
+    let prefix_token = branch.take_next_token_of(&_PREFIX_TOKENS)?;
+    let expression = parse_expression(branch, Precedence::Prefix)?;
     Some(Prefix::new(prefix_token, expression))
   }
 }
@@ -219,11 +504,16 @@ impl Parsable for Prefix {
 mod test {
   use crate::{
     ast::{
-      AstNode, Bool, Expression, Ident, If, Int, LetStatement, NodeFormatter, Prefix, StringLiteral,
+      AstNode, Block, Bool, Expression, Func, Ident, If, Int, LetStatement, NodeFormatter, Prefix,
+      Program, Statement, StringLiteral,
     },
-    branch::BranchRoot,
-    lexer::Lexer,
-    parser::parser::Parser,
+    branch::{Branch, BranchRoot},
+    lexer::{Keywords, LexError, Lexer},
+    parser::{
+      parser::{Parser, ParserBranchData, MAX_NESTING_DEPTH},
+      ParseError, ParseErrorKind,
+    },
+    token::TokenKind,
   };
 
   #[test]
@@ -349,4 +639,503 @@ mod test {
     let prefix = NodeFormatter::new(source, &prefix).to_string();
     assert_eq!(prefix, "-5");
   }
+
+  #[test]
+  fn infix_precedence_test() {
+    let cases = [
+      ("a + b;", "(a + b)"),
+      ("a + b * c;", "(a + (b * c))"),
+      ("a * b + c;", "((a * b) + c)"),
+      ("a - b - c;", "((a - b) - c)"),
+      ("a / b * c;", "((a / b) * c)"),
+      ("(a + b) * c;", "((a + b) * c)"),
+      ("a + b < c * d == true;", "(((a + b) < (c * d)) == true)"),
+      ("5 > 4 != 3 < 4;", "((5 > 4) != (3 < 4))"),
+      ("-a * b;", "(-a * b)"),
+      ("!-a;", "!-a"),
+      ("a + add(b * c) + d;", "((a + add((b * c))) + d)"),
+      (
+        "add(a, b, 1, 2 * 3, 4 + 5);",
+        "add(a, b, 1, (2 * 3), (4 + 5))",
+      ),
+      ("sumador(2)(5);", "sumador(2)(5)"),
+      ("f();", "f()"),
+      ("a % b * c;", "((a % b) * c)"),
+      ("a + b % c;", "(a + (b % c))"),
+      ("a <= b == b >= c;", "((a <= b) == (b >= c))"),
+      ("a || b && c;", "(a || (b && c))"),
+      ("a && b || c;", "((a && b) || c)"),
+      ("a == b && c != d;", "((a == b) && (c != d))"),
+      ("!a || b;", "(!a || b)"),
+      ("[1, 2 * 3, [a]];", "[1, (2 * 3), [a]]"),
+      ("a * [1, 2][b + 1];", "(a * ([1, 2][(b + 1)]))"),
+      ("-a[0];", "-(a[0])"),
+      ("f(a[0])[1];", "(f((a[0]))[1])"),
+      ("m[0][1](2);", "((m[0])[1])(2)"),
+    ];
+
+    for (source, expected) in cases {
+      let lexer = Lexer::new(&source);
+
+      let mut parser = Parser::new(lexer);
+      let program: Program = parser.branch().inspect().unwrap();
+      let program = NodeFormatter::new(source, &program).to_string();
+      assert_eq!(program, format!("{expected};"), "parsing `{source}`");
+    }
+  }
+
+  #[test]
+  fn hash_literal_test() {
+    let cases = [
+      (
+        r#"let h = {"uno": 1, 2: a + b, true: [3]};"#,
+        "let h = {uno: 1, 2: (a + b), true: [3]};",
+      ),
+      ("let h = {};", "let h = {};"),
+      (r#"{"a": 1}["a"];"#, "({a: 1}[a]);"),
+      ("{a: {b: 1}};", "{a: {b: 1}};"),
+      // blocks win at the start of a statement, and display their statements
+      ("{}", ";"),
+      ("{ a; }", "a;"),
+      ("if (a) { b } else { {} }", "if(a) {b} else {};"),
+      ("f({});", "f({});"),
+    ];
+
+    for (source, expected) in cases {
+      let lexer = Lexer::new(&source);
+      let mut parser = Parser::new(lexer);
+      let program = parser.parse_program();
+      assert!(parser.errors().is_empty(), "parsing `{source}`");
+      let program = NodeFormatter::new(source, &program).to_string();
+      assert_eq!(program, expected, "parsing `{source}`");
+    }
+
+    let program = crate::parse("{}").unwrap();
+    assert!(matches!(program.statements(), [Statement::Block(_)]));
+    let errors = crate::parse("let h = {a: 1 b};").err().unwrap();
+    assert_eq!(
+      errors[0].to_string(),
+      "1:15: unexpected identifier, expected one of `,`, `}`"
+    );
+  }
+
+  #[test]
+  fn unclosed_array_error_test() {
+    let errors = crate::parse("[1, 2;").err().unwrap();
+    assert_eq!(
+      errors[0].to_string(),
+      "1:6: unexpected `;`, expected one of `,`, `]`"
+    );
+  }
+
+  #[test]
+  fn infix_missing_rhs_test() {
+    let source = " a + ";
+    let lexer = Lexer::new(&source);
+
+    let mut parser = Parser::new(lexer);
+    assert!(parser.branch().inspect::<Expression>().is_none());
+  }
+
+  #[test]
+  fn func_parse_test() {
+    let source = " fn(x, y) { return x + y; } ";
+    let lexer = Lexer::new(&source);
+
+    let mut parser = Parser::new(lexer);
+    let func: Func = parser.branch().inspect().unwrap();
+    assert_eq!(func.params().len(), 2);
+    let func = NodeFormatter::new(source, &func).to_string();
+    assert_eq!(func, "fn(x, y) {return (x + y)}");
+
+    let source = " fn() {} ";
+    let lexer = Lexer::new(&source);
+
+    let mut parser = Parser::new(lexer);
+    let func: Func = parser.branch().inspect().unwrap();
+    assert!(func.params().is_empty());
+  }
+
+  #[test]
+  fn readme_parse_test() {
+    let source = r#"
+let mayor_de_edad = fn(edad) {
+  if(edad > 18) {
+    return true;
+  } else {
+    return false;
+  }
+};
+let sumador = fn(x) {
+  return fn(y) {
+    return x + y;
+  };
+};
+mayor_de_edad(sumador(5)(20));
+"#;
+    let lexer = Lexer::new(&source);
+
+    let mut parser = Parser::new(lexer);
+    let program: Program = parser.branch().inspect().unwrap();
+    let program = NodeFormatter::new(source, &program).to_string();
+    assert_eq!(
+      program,
+      "let mayor_de_edad = fn(edad) {if((edad > 18)) {return true} else {return false}};\
+       let sumador = fn(x) {return fn(y) {return (x + y)}};\
+       mayor_de_edad(sumador(5)(20));"
+    );
+  }
+
+  #[test]
+  fn node_span_test() {
+    let source = "let a = add(1,\n  2 * 3);\nif (a) { a; } else { 0; }";
+    let lexer = Lexer::new(&source);
+
+    let mut parser = Parser::new(lexer);
+    let program: Program = parser.branch().inspect().unwrap();
+    let spans: Vec<_> = program
+      .statements()
+      .iter()
+      .map(|st| &source[st.span().range()])
+      .collect();
+    assert_eq!(
+      spans,
+      ["let a = add(1,\n  2 * 3)", "if (a) { a; } else { 0; }"]
+    );
+
+    let start = parser.position(program.statements()[1].span().start);
+    assert_eq!((start.line, start.column), (3, 1));
+  }
+
+  fn parse_errors(source: &str) -> Vec<ParseError> {
+    let lexer = Lexer::new(&source);
+    let mut parser = Parser::new(lexer);
+    parser.parse_program();
+    parser.errors()
+  }
+
+  #[test]
+  fn missing_semicolon_error_test() {
+    let errors = parse_errors("let a = 5;\nlet b = 5 6;");
+    assert_eq!(errors.len(), 1);
+    let error = &errors[0];
+
+    assert_eq!(error.token().map(|t| t.kind()), Some(TokenKind::Int));
+    assert_eq!(error.expected(), [TokenKind::Semicolon]);
+    assert_eq!(error.span().range(), 21..22);
+    assert_eq!(error.to_string(), "2:11: unexpected integer, expected `;`");
+  }
+
+  #[test]
+  fn expression_statement_semicolon_test() {
+    let errors = parse_errors("5 6;");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+      errors[0].to_string(),
+      "1:3: unexpected integer, expected `;`"
+    );
+
+    let errors = parse_errors("let x = 1; x y;");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+      errors[0].to_string(),
+      "1:14: unexpected identifier, expected `;`"
+    );
+
+    assert!(parse_errors("if (x) { x } fn(x) { x } { x }").is_empty());
+  }
+
+  #[test]
+  fn expected_token_set_error_test() {
+    let errors = parse_errors("let = 5;");
+    assert_eq!(
+      errors[0].to_string(),
+      "1:5: unexpected `=`, expected identifier"
+    );
+
+    let errors = parse_errors("f(1, 2");
+    assert!(matches!(
+      errors[0].kind(),
+      ParseErrorKind::UnexpectedEof { .. }
+    ));
+    assert_eq!(errors[0].expected(), [TokenKind::Comma, TokenKind::RParen]);
+
+    let errors = parse_errors("let a = ;");
+    let expected = errors[0].expected();
+    assert!(expected.contains(&TokenKind::Ident));
+    assert!(expected.contains(&TokenKind::Func));
+  }
+
+  #[test]
+  fn spanish_keywords_error_test() {
+    let source = "variable variable = 1;\n)";
+    let lexer = Lexer::with_keywords(&source, Keywords::spanish());
+    let mut parser = Parser::new(lexer);
+    parser.parse_program();
+    let errors: Vec<_> = parser.errors().iter().map(|e| e.to_string()).collect();
+
+    assert_eq!(
+      errors[0],
+      "1:10: unexpected `variable`, expected identifier"
+    );
+    let last = errors.last().unwrap();
+    assert!(
+      last.starts_with("2:1: unexpected `)`, expected one of `variable`, `regresa`, `{`"),
+      "{errors:?}"
+    );
+    assert!(last.contains("`si`, `procedimiento`"), "{errors:?}");
+  }
+
+  #[test]
+  fn unterminated_string_error_test() {
+    let errors = parse_errors("let a = 1;\nlet s = \"hola");
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(
+      errors[0].kind(),
+      &ParseErrorKind::Lex(LexError::UnterminatedString)
+    );
+    assert_eq!(errors[0].to_string(), "2:9: unterminated string");
+
+    let errors = parse_errors("let a = 1;\nlet b = /* dos");
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(
+      errors[0].kind(),
+      &ParseErrorKind::Lex(LexError::UnterminatedComment)
+    );
+  }
+
+  #[test]
+  fn parse_expression_lex_errors_test() {
+    let errors = crate::parse_expression("99999999999999999999")
+      .err()
+      .unwrap();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(errors[0].to_string(), "1:1: integer literal is too large");
+
+    let errors = crate::parse_expression("\"a\\qb\"").err().unwrap();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(errors[0].to_string(), "1:3: invalid escape sequence");
+  }
+
+  #[test]
+  fn invalid_escape_error_test() {
+    let source = "let a = \"x\\qy\";\nlet b = ;";
+    let lexer = Lexer::new(&source);
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+
+    let errors: Vec<_> = parser.errors().iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert_eq!(errors[0], "1:11: invalid escape sequence");
+    assert!(errors[1].starts_with("2:9: unexpected `;`"));
+    assert_eq!(program.statements().len(), 1);
+  }
+
+  #[test]
+  fn unescaped_brace_error_test() {
+    let errors: Vec<_> = parse_errors("\"{\";")
+      .iter()
+      .map(|e| e.to_string())
+      .collect();
+    assert_eq!(
+      errors,
+      ["1:2: unclosed interpolation, write `\\{` for a literal `{`"]
+    );
+
+    let errors: Vec<_> = parse_errors("let a = \"{}\";\nlet b = \"{\"; b;")
+      .iter()
+      .map(|e| e.to_string())
+      .collect();
+    assert_eq!(
+      errors,
+      [
+        "1:10: empty interpolation, write `\\{` for a literal `{`",
+        "2:10: unclosed interpolation, write `\\{` for a literal `{`",
+      ]
+    );
+
+    // the `"` after `{` closes the literal, so what follows isn't reread
+    let errors: Vec<_> = parse_errors("let a = \"x\"; \"{\" + a + \"}\";")
+      .iter()
+      .map(|e| e.to_string())
+      .collect();
+    assert_eq!(
+      errors,
+      ["1:15: unclosed interpolation, write `\\{` for a literal `{`"]
+    );
+  }
+
+  #[test]
+  fn integer_overflow_error_test() {
+    let source = "let a = 1;\nlet b = 0x1_0000_0000_0000_0000;";
+    let errors = crate::parse(source).err().unwrap();
+    let errors: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors, ["2:9: integer literal is too large"]);
+
+    // the `-` is a prefix operator, so the literal itself is too large
+    let errors = crate::parse("-9223372036854775808;").err().unwrap();
+    let errors: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors, ["1:2: integer literal is too large"]);
+  }
+
+  #[test]
+  fn lossless_lexer_test() {
+    let source = "let a = 5; // cinco\na + 1;\n";
+    let lexer = Lexer::new(&source).lossless();
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    assert!(parser.errors().is_empty());
+    assert_eq!(program.statements().len(), 2);
+  }
+
+  #[test]
+  fn template_string_test() {
+    let source = r#""hola {nombre}, tienes {edad + 1} años";"#;
+    let lexer = Lexer::new(&source);
+    let mut parser = Parser::new(lexer);
+    let program: Program = parser.branch().inspect().unwrap();
+    let program = NodeFormatter::new(source, &program).to_string();
+    assert_eq!(program, "hola {nombre}, tienes {(edad + 1)} años;");
+
+    let errors = crate::parse(r#""a {b c}";"#).err().unwrap();
+    assert_eq!(
+      errors[0].to_string(),
+      "1:7: unexpected identifier, expected `}`"
+    );
+    assert!(crate::parse(r#""a {}";"#).is_err());
+  }
+
+  #[test]
+  fn memoized_inspect_test() {
+    let source = "if (x) { 1 } else { 2 } + 3";
+    let lexer = Lexer::new(&source);
+    let parser = Parser::new(lexer);
+
+    let mut data = ParserBranchData::default();
+    let mut branch = Branch::new(&parser, &mut data);
+    let first: Expression = branch.inspect().unwrap();
+    let end = branch.token_pos;
+    assert!(parser.memoized::<Block>(4).unwrap().is_some());
+    assert!(parser.memoized::<Ident>(5).unwrap().is_none());
+
+    let mut data = ParserBranchData::default();
+    let mut branch = Branch::new(&parser, &mut data);
+    let second: Expression = branch.inspect().unwrap();
+    assert_eq!(branch.token_pos, end);
+    assert_eq!(
+      NodeFormatter::new(source, &first).to_string(),
+      NodeFormatter::new(source, &second).to_string()
+    );
+  }
+
+  #[test]
+  fn nesting_limit_test() {
+    let nested_arrays = |depth: usize| format!("{}1{};", "[".repeat(depth), "]".repeat(depth));
+    assert!(parse_errors(&nested_arrays(MAX_NESTING_DEPTH - 1)).is_empty());
+
+    let source = format!("{}\nlet a = 1;", nested_arrays(MAX_NESTING_DEPTH));
+    let lexer = Lexer::new(&source.as_str());
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    let errors = parser.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind(), &ParseErrorKind::NestingTooDeep);
+    assert_eq!(
+      errors[0].to_string(),
+      format!(
+        "1:{}: nesting is deeper than {MAX_NESTING_DEPTH} levels",
+        MAX_NESTING_DEPTH + 1
+      )
+    );
+    assert_eq!(program.statements().len(), 1);
+
+    // deep enough to overflow the stack without the limit
+    let source = format!("{}x;{}", "if (x) { ".repeat(1_000), " }".repeat(1_000));
+    let errors = parse_errors(&source);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind(), &ParseErrorKind::NestingTooDeep);
+  }
+
+  #[test]
+  fn valid_program_has_no_errors_test() {
+    assert!(parse_errors("let a = fn(x) { x; }; a(1);").is_empty());
+  }
+
+  #[test]
+  fn literal_values_after_recovery_test() {
+    // the skipped statement has literals, some of them never parsed
+    let source = r#"let a = "uno {1 +}" 2.5; let b = 3; "tres {b}";"#;
+    let lexer = Lexer::new(&source);
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    assert_eq!(parser.errors().len(), 1);
+    let program = NodeFormatter::new(source, &program).to_string();
+    assert_eq!(program, "let b = 3;tres {b};");
+    assert!(parser.value_at(0).is_none());
+    assert_eq!(
+      parser.value_at(3).and_then(|v| v.to_string()),
+      Some("uno ".into())
+    );
+  }
+
+  #[test]
+  fn error_recovery_test() {
+    let source = r#"
+let a = 5 6;
+let b = 10;
+let f = fn(x) {
+  let y = ;
+  return y;
+};
+let c = a + b;
+return ) ;
+c;
+"#;
+    let lexer = Lexer::new(&source);
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+
+    let errors: Vec<_> = parser.errors().iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors.len(), 3, "{errors:?}");
+    assert!(errors[0].starts_with("2:11: unexpected integer"));
+    assert!(errors[1].starts_with("5:11: unexpected `;`"));
+    assert!(errors[2].starts_with("9:8: unexpected `)`"));
+
+    let program = NodeFormatter::new(source, &program).to_string();
+    assert_eq!(program, "let b = 10;let c = (a + b);c;");
+  }
+
+  #[test]
+  fn error_recovery_stops_at_keyword_test() {
+    let source = "let a = 5\nlet b = 6;\n}\nb;";
+    let lexer = Lexer::new(&source);
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 2);
+    let program = NodeFormatter::new(source, &program).to_string();
+    assert_eq!(program, "let b = 6;b;");
+  }
+
+  #[test]
+  fn error_recovery_unclosed_brace_test() {
+    let cases = [
+      ("let a = {1: ;\nlet b = 2;\nb;", "let b = 2;b;"),
+      ("let f = fn(x) {\n  x +\nreturn 1;", "return 1;"),
+      // braces closed further on still skip the whole statement
+      (
+        "let a = {1: ;\nlet b = {2: 3};\n};\nlet c = 4;",
+        "let c = 4;",
+      ),
+    ];
+    for (source, expected) in cases {
+      let lexer = Lexer::new(&source);
+      let mut parser = Parser::new(lexer);
+      let program = parser.parse_program();
+
+      assert_eq!(parser.errors().len(), 1, "parsing `{source}`");
+      let program = NodeFormatter::new(source, &program).to_string();
+      assert_eq!(program, expected, "parsing `{source}`");
+    }
+  }
 }
@@ -1,39 +1,95 @@
-use dupe::OptionDupedExt;
+use dupe::Dupe;
 
+use crate::ast::{Expression, Program};
 use crate::branch::{Branch, BranchData, BranchRoot};
-use crate::lexer::{Lexer, Source};
+use crate::interner::Symbol;
+use crate::lexer::{Lexer, LexerStatus, Position, Source, Span};
 use crate::token::{Token, TokenKind, TokenValue};
 use crate::types::DefaultCell;
-use std::cell::RefCell;
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
 use std::iter::Iterator;
 
+use super::error::{ParseError, ParseErrorKind};
+
 #[repr(u8)]
-enum Precedence {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Precedence {
   Lowest = 1,
-  Equals = 2,
-  LessGreater = 3,
-  Sum = 4,
-  Product = 5,
-  Prefix = 6,
-  Call = 7,
+  Or = 2,
+  And = 3,
+  Equals = 4,
+  LessGreater = 5,
+  Sum = 6,
+  Product = 7,
+  Prefix = 8,
+  Call = 9,
+}
+
+impl Precedence {
+  /// Binding power of `kind` when it appears after a complete expression
+  pub(crate) fn of_infix(kind: TokenKind) -> Precedence {
+    match kind {
+      TokenKind::Or => Precedence::Or,
+      TokenKind::And => Precedence::And,
+      TokenKind::Eq | TokenKind::NotEq => Precedence::Equals,
+      TokenKind::LT | TokenKind::GT | TokenKind::LTEq | TokenKind::GTEq => Precedence::LessGreater,
+      TokenKind::Plus | TokenKind::Minus => Precedence::Sum,
+      TokenKind::Division | TokenKind::Mul | TokenKind::Mod => Precedence::Product,
+      TokenKind::LParen | TokenKind::LBracket => Precedence::Call,
+      _ => Precedence::Lowest,
+    }
+  }
 }
 
+/// How deep expressions and blocks can be nested in each other. Deeper input
+/// fails with [`ParseErrorKind::NestingTooDeep`] instead of overflowing the
+/// stack, since each level is parsed recursively.
+pub const MAX_NESTING_DEPTH: usize = 128;
+
 #[derive(Debug)]
 pub struct Parser<S> {
   lexer: RefCell<Lexer<S>>,
-  tokens: RefCell<Vec<Token>>,
+  /// Read tokens along with their values
+  tokens: RefCell<Vec<(Token, Option<TokenValue>)>>,
   errors: DefaultCell<Vec<ParseError>>,
-  values: DefaultCell<Vec<TokenValue>>,
+  failure: RefCell<Failure>,
+  /// Indexed by token position
+  memo: RefCell<Vec<Vec<MemoEntry>>>,
+  /// Expressions and blocks being parsed, from the outermost one
+  depth: Cell<usize>,
+  /// Token position where [`MAX_NESTING_DEPTH`] was exceeded, which is
+  /// reported instead of the tokens expected there
+  too_deep: Cell<Option<usize>>,
   branch_data: ParserBranchData,
 }
 
+/// Outcome of inspecting a node at some token position: the node along with
+/// the branch data right after it, or `None` if it didn't match
+pub(crate) type Memo<T> = Option<(T, ParserBranchData)>;
+
+/// Inspected node type, with the boxed `(node, end)` pair if it matched
+type MemoEntry = (TypeId, Option<Box<dyn Any>>);
+
+/// Farthest token position where a match failed, along with the token kinds
+/// that were tried there. It's what gets reported when the parser gets stuck,
+/// since backtracking makes every earlier failure expected.
+#[derive(Debug, Default)]
+struct Failure {
+  token_pos: usize,
+  expected: Vec<TokenKind>,
+}
+
 impl<S> Parser<S> {
   pub fn new(lexer: Lexer<S>) -> Parser<S> {
     Parser {
       lexer: RefCell::new(lexer),
       tokens: RefCell::default(),
       errors: DefaultCell::default(),
-      values: DefaultCell::default(),
+      failure: RefCell::default(),
+      memo: RefCell::default(),
+      depth: Cell::default(),
+      too_deep: Cell::default(),
       branch_data: ParserBranchData::default(),
     }
   }
@@ -42,27 +98,182 @@ impl<S> Parser<S> {
     let mut errors = self.errors.borrow_mut();
     errors.push(error);
   }
-  pub fn value_at(&self, index: usize) -> Option<TokenValue> {
-    let values = self.values.lazy_borrow()?;
-    values.get(index).duped()
+  /// Errors recorded so far
+  pub fn errors(&self) -> Vec<ParseError> {
+    match self.errors.lazy_borrow() {
+      Some(errors) => errors.clone(),
+      None => Vec::new(),
+    }
+  }
+  /// Records that a token of `kind` was needed at `token_pos`
+  pub(crate) fn expect(&self, token_pos: usize, kind: TokenKind) {
+    let mut failure = self.failure.borrow_mut();
+    if token_pos > failure.token_pos {
+      failure.token_pos = token_pos;
+      failure.expected.clear();
+    }
+    if token_pos == failure.token_pos && !failure.expected.contains(&kind) {
+      failure.expected.push(kind);
+    }
+  }
+  /// Runs `parse` one nesting level deeper, failing at `token_pos` when that
+  /// goes past [`MAX_NESTING_DEPTH`]
+  pub(crate) fn nested<T>(&self, token_pos: usize, parse: impl FnOnce() -> Option<T>) -> Option<T> {
+    let depth = self.depth.get();
+    if depth >= MAX_NESTING_DEPTH {
+      let mut failure = self.failure.borrow_mut();
+      if token_pos > failure.token_pos {
+        failure.token_pos = token_pos;
+        failure.expected.clear();
+      }
+      self.too_deep.set(Some(token_pos));
+      return None;
+    }
+    self.depth.set(depth + 1);
+    let node = parse();
+    self.depth.set(depth);
+    node
+  }
+  /// Outcome of a previous inspection of a `T` at `token_pos`
+  pub(crate) fn memoized<T: Clone + 'static>(&self, token_pos: usize) -> Option<Memo<T>> {
+    let memo = self.memo.borrow();
+    let type_id = TypeId::of::<T>();
+    let (_, entry) = memo.get(token_pos)?.iter().find(|(id, _)| *id == type_id)?;
+    match entry {
+      Some(entry) => entry
+        .downcast_ref::<(T, ParserBranchData)>()
+        .cloned()
+        .map(Some),
+      None => Some(None),
+    }
+  }
+  pub(crate) fn memoize<T: 'static>(&self, token_pos: usize, entry: Memo<T>) {
+    let mut memo = self.memo.borrow_mut();
+    if memo.len() <= token_pos {
+      memo.resize_with(token_pos + 1, Vec::new);
+    }
+    let entry = entry.map(|entry| Box::new(entry) as Box<dyn Any>);
+    memo[token_pos].push((TypeId::of::<T>(), entry));
   }
 }
 impl<S: Source> Parser<S> {
   pub fn token_at(&self, index: usize) -> Option<Token> {
+    self.entry_at(index).map(|(token, _)| token)
+  }
+  /// Value of the token at `index`, for literals
+  pub fn value_at(&self, index: usize) -> Option<TokenValue> {
+    self.entry_at(index)?.1
+  }
+  /// Token at `index` along with its value, reading it if needed
+  fn entry_at(&self, index: usize) -> Option<(Token, Option<TokenValue>)> {
     let mut tokens = self.tokens.borrow_mut();
     let mut lexer = self.lexer.borrow_mut();
 
     while tokens.len() <= index {
-      let Some((token, value)) = lexer.next() else {
-        self.add_error(ParseError::NoMoreTokens);
+      let seen_errors = lexer.errors().len();
+      let (token, value) = lexer.next()?;
+      for error in &lexer.errors()[seen_errors..] {
+        let span = error.span().unwrap_or(token.span());
+        let kind = ParseErrorKind::Lex(*error);
+        self.add_error(ParseError::new(kind, span, lexer.position(span.start)));
+      }
+      if token.kind() == TokenKind::Comment {
+        continue;
+      }
+      // end marker of lossless lexers
+      if token.kind() == TokenKind::EOF && token.span().range().is_empty() {
         return None;
-      };
-      tokens.push(token);
-      if let Some(value) = value {
-        self.values.borrow_mut().push(value)
       }
+      tokens.push((token, value));
     }
-    tokens.get(index).duped()
+    tokens.get(index).cloned()
+  }
+  /// Parses the whole input as a program. Statements with errors are left
+  /// out of it and reported in [`Parser::errors`].
+  pub fn parse_program(&mut self) -> Program {
+    self
+      .branch()
+      .inspect()
+      .unwrap_or_else(|| Program::new(Vec::new()))
+  }
+  /// Parses the whole input as a single expression, optionally followed by
+  /// a `;`. Errors are reported in [`Parser::errors`].
+  pub fn parse_expression(&mut self) -> Option<Expression> {
+    let mut branch = self.branch();
+    let expression = branch.inspect::<Expression>();
+    if expression.is_some() {
+      branch.take_next_token_by_kind(TokenKind::Semicolon);
+    }
+    let token_pos = branch.token_pos;
+
+    if self.token_at(token_pos).is_some() {
+      self.expect(token_pos, TokenKind::EOF);
+    }
+    if expression.is_none() || self.token_at(token_pos).is_some() || self.lexer_failed() {
+      self.add_error(self.failure_error());
+      return None;
+    }
+    expression
+  }
+  /// Token position where the last failure happened
+  pub(crate) fn failure_pos(&self) -> usize {
+    self.failure.borrow().token_pos
+  }
+  /// Whether the lexer stopped before the end of the source
+  pub(crate) fn lexer_failed(&self) -> bool {
+    matches!(self.lexer.borrow().status(), LexerStatus::ErrorAt(_))
+  }
+  /// Builds the error for the farthest point the parser could reach
+  pub(crate) fn failure_error(&self) -> ParseError {
+    let token_pos = self.failure.borrow().token_pos;
+    let found = self.token_at(token_pos);
+
+    let lexer_error = self.lexer.borrow().error();
+    if let (LexerStatus::ErrorAt(end), Some(error)) = (self.lexer.borrow().status(), lexer_error) {
+      let start = self.lexer.borrow().offset();
+      let kind = ParseErrorKind::Lex(error);
+      return ParseError::new(kind, Span::new(start, end), self.position(start));
+    }
+
+    let expected = std::mem::take(&mut self.failure.borrow_mut().expected);
+    // memoized failures don't record what they expected again
+    self.memo.borrow_mut().clear();
+    if let Some(token_pos) = self.too_deep.take() {
+      let kind = ParseErrorKind::NestingTooDeep;
+      let span = match self.token_at(token_pos) {
+        Some(token) => token.span(),
+        None => {
+          let end = self.lexer.borrow().source().len();
+          Span::new(end, end)
+        }
+      };
+      return ParseError::new(kind, span, self.position(span.start));
+    }
+    let error = match found {
+      Some(found) => {
+        let span = found.span();
+        let kind = ParseErrorKind::UnexpectedToken { found, expected };
+        ParseError::new(kind, span, self.position(span.start))
+      }
+      None => {
+        let end = self.lexer.borrow().source().len();
+        let kind = ParseErrorKind::UnexpectedEof { expected };
+        ParseError::new(kind, Span::new(end, end), self.position(end))
+      }
+    };
+    error.with_keywords(self.lexer.borrow().keywords().dupe())
+  }
+  /// Line and column of `offset`, which must belong to an already read token
+  pub fn position(&self, offset: usize) -> Position {
+    self.lexer.borrow().position(offset)
+  }
+  pub(crate) fn intern(&self, token: &Token) -> Symbol {
+    let lexer = self.lexer.borrow();
+    Symbol::intern(token.literal(lexer.source()))
+  }
+  pub(crate) fn token_literal(&self, token: &Token) -> String {
+    let lexer = self.lexer.borrow();
+    token.literal(lexer.source()).to_string()
   }
 }
 
@@ -70,12 +281,10 @@ impl BranchData for ParserBranchData {
   fn child_data(&self) -> Self {
     ParserBranchData {
       token_pos: self.token_pos,
-      value_idx: self.value_idx,
     }
   }
   fn update_from(&mut self, other: Self) {
     self.token_pos = other.token_pos;
-    self.value_idx = other.value_idx;
   }
 }
 
@@ -89,18 +298,11 @@ impl<S: Source> BranchRoot for Parser<S> {
   }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ParserBranchData {
   pub(crate) token_pos: usize,
-  pub(crate) value_idx: usize,
 }
 
-#[derive(Debug)]
-pub enum ParseError {
-  Msg(String),
-  InvalidValueFormat(String),
-  NoMoreTokens,
-}
 type ParserBranch<'p, S> = Branch<'p, Parser<S>>;
 
 impl<'p, S: Source> ParserBranch<'p, S> {
@@ -110,11 +312,18 @@ impl<'p, S: Source> ParserBranch<'p, S> {
     self.token_pos += 1;
     return Some(token);
   }
+  pub(crate) fn peek_next_token(&self) -> Option<Token> {
+    self.root().token_at(self.token_pos)
+  }
   pub(crate) fn take_next_token_by_kind(&mut self, kind: TokenKind) -> Option<Token> {
     let token_pos = self.token_pos;
-    let token = self.root().token_at(token_pos)?;
+    let Some(token) = self.root().token_at(token_pos) else {
+      self.root().expect(token_pos, kind);
+      return None;
+    };
 
     if token.kind() != kind {
+      self.root().expect(token_pos, kind);
       return None;
     }
 
@@ -122,14 +331,19 @@ impl<'p, S: Source> ParserBranch<'p, S> {
     return Some(token);
   }
 
-  pub(crate) fn take_next_value(&mut self) -> Option<TokenValue> {
-    let index = self.value_idx;
-
-    let token_value = self.root().value_at(index)?;
-
-    self.value_idx += 1;
+  /// Takes the next token if it's of any of the given `kinds`
+  pub(crate) fn take_next_token_of(&mut self, kinds: &[TokenKind]) -> Option<Token> {
+    kinds
+      .iter()
+      .find_map(|kind| self.take_next_token_by_kind(*kind))
+  }
 
-    Some(token_value)
+  /// Takes the next token if it's of `kind`, along with its value
+  pub(crate) fn take_next_literal(&mut self, kind: TokenKind) -> Option<(Token, TokenValue)> {
+    let token_pos = self.token_pos;
+    let token = self.take_next_token_by_kind(kind)?;
+    let value = self.root().value_at(token_pos)?;
+    Some((token, value))
   }
 }
 
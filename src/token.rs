@@ -1,99 +1,224 @@
-use std::{
-  ops::{Deref, Range},
-  rc::Rc,
-};
+use std::{fmt, ops::Range, rc::Rc};
 
 use dupe::Dupe;
 
-use crate::types::Literal;
+use crate::lexer::Span;
 
-#[derive(Clone, Debug, Dupe, PartialEq, Eq)]
+#[derive(Clone, Dupe, PartialEq, Eq)]
 pub struct Token {
   kind: TokenKind,
   start: usize,
   end: usize,
+  /// Only set by lossless lexers
+  trivia: Option<Rc<TokenTrivia>>,
+}
+
+/// Whitespace and comments around a token
+#[derive(Debug, PartialEq, Eq)]
+struct TokenTrivia {
+  leading: Vec<Trivia>,
+  trailing: Vec<Trivia>,
+}
+
+/// Piece of source that isn't part of any token
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Trivia {
+  pub kind: TriviaKind,
+  pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Dupe)]
+pub enum TriviaKind {
+  Whitespace,
+  Comment,
 }
 
 impl Token {
   pub fn new(kind: TokenKind, start: usize, end: usize) -> Token {
-    Token { kind, start, end }
+    Token {
+      kind,
+      start,
+      end,
+      trivia: None,
+    }
+  }
+
+  pub(crate) fn with_trivia(mut self, leading: Vec<Trivia>, trailing: Vec<Trivia>) -> Token {
+    self.trivia = Some(Rc::new(TokenTrivia { leading, trailing }));
+    self
   }
 
   pub fn range(&self) -> Range<usize> {
     self.start..self.end
   }
 
+  pub fn span(&self) -> Span {
+    Span::new(self.start, self.end)
+  }
+
   pub fn kind(&self) -> TokenKind {
     self.kind
   }
+
+  /// Trivia between the previous token's trailing trivia and this token
+  pub fn leading_trivia(&self) -> &[Trivia] {
+    self.trivia.as_ref().map_or(&[], |trivia| &trivia.leading)
+  }
+
+  /// Trivia after this token up to the end of its line. The last token also
+  /// takes the trivia up to the end of the source.
+  pub fn trailing_trivia(&self) -> &[Trivia] {
+    self.trivia.as_ref().map_or(&[], |trivia| &trivia.trailing)
+  }
+
+  /// Span of the token along with its trivia
+  pub fn full_span(&self) -> Span {
+    let start = self
+      .leading_trivia()
+      .first()
+      .map_or(self.start, |t| t.span.start);
+    let end = self
+      .trailing_trivia()
+      .last()
+      .map_or(self.end, |t| t.span.end);
+    Span::new(start, end)
+  }
+}
+
+impl fmt::Debug for Token {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut debug = f.debug_struct("Token");
+    debug
+      .field("kind", &self.kind)
+      .field("start", &self.start)
+      .field("end", &self.end);
+    if let Some(trivia) = &self.trivia {
+      debug
+        .field("leading", &trivia.leading)
+        .field("trailing", &trivia.trailing);
+    }
+    debug.finish()
+  }
 }
 
 /// Supported `TokenType`s in LPP
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Dupe)]
 pub enum TokenKind {
+  And,
   Assign,
+  Colon,
   Comma,
+  Comment,
   Division,
   Else,
   EOF,
   Eq,
   False,
+  Float,
   Func,
   GT,
+  GTEq,
   Ident,
   If,
   Illegal,
   Int,
   LBrace,
+  LBracket,
   Let,
   LParen,
   LT,
+  LTEq,
   Minus,
+  Mod,
   Mul, // Multiplication
   Neg, // Negation
   NotEq,
+  Or,
   Plus,
   Return,
   RParen,
   RBrace,
+  RBracket,
   Semicolon,
   String,
+  /// `"text{`, first segment of a string with interpolations
+  TemplateStart,
+  /// `}text{`, between two interpolations
+  TemplateMiddle,
+  /// `}text"`, last segment of a string with interpolations
+  TemplateEnd,
   True,
 }
 
-impl TokenKind {
-  pub fn from_literal<'s>(lit: Literal<'s>) -> TokenKind {
-    match LITERALS.binary_search_by(|(text, _)| text.cmp(&lit.deref())) {
-      Ok(idx) => LITERALS[idx].1,
-      Err(_) => TokenKind::Ident,
-    }
+impl std::fmt::Display for TokenKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let text = match self {
+      TokenKind::And => "`&&`",
+      TokenKind::Assign => "`=`",
+      TokenKind::Colon => "`:`",
+      TokenKind::Comma => "`,`",
+      TokenKind::Comment => "comment",
+      TokenKind::Division => "`/`",
+      TokenKind::Else => "`else`",
+      TokenKind::EOF => "end of file",
+      TokenKind::Eq => "`==`",
+      TokenKind::False => "`false`",
+      TokenKind::Float => "float",
+      TokenKind::Func => "`fn`",
+      TokenKind::GT => "`>`",
+      TokenKind::GTEq => "`>=`",
+      TokenKind::Ident => "identifier",
+      TokenKind::If => "`if`",
+      TokenKind::Illegal => "illegal character",
+      TokenKind::Int => "integer",
+      TokenKind::LBrace => "`{`",
+      TokenKind::LBracket => "`[`",
+      TokenKind::Let => "`let`",
+      TokenKind::LParen => "`(`",
+      TokenKind::LT => "`<`",
+      TokenKind::LTEq => "`<=`",
+      TokenKind::Minus => "`-`",
+      TokenKind::Mod => "`%`",
+      TokenKind::Mul => "`*`",
+      TokenKind::Neg => "`!`",
+      TokenKind::NotEq => "`!=`",
+      TokenKind::Or => "`||`",
+      TokenKind::Plus => "`+`",
+      TokenKind::Return => "`return`",
+      TokenKind::RParen => "`)`",
+      TokenKind::RBrace => "`}`",
+      TokenKind::RBracket => "`]`",
+      TokenKind::Semicolon => "`;`",
+      TokenKind::String => "string",
+      TokenKind::TemplateStart => "template string",
+      TokenKind::TemplateMiddle | TokenKind::TemplateEnd => "`}`",
+      TokenKind::True => "`true`",
+    };
+    write!(f, "{text}")
   }
 }
 
-static LITERALS: [(&str, TokenKind); 7] = [
-  ("else", TokenKind::Else),
-  ("false", TokenKind::False),
-  ("fn", TokenKind::Func),
-  ("if", TokenKind::If),
-  ("let", TokenKind::Let),
-  ("return", TokenKind::Return),
-  ("true", TokenKind::True),
-];
-
 #[derive(Debug, Clone, Dupe)]
 pub enum TokenValue {
-  Int(u32),
+  Int(i64),
+  Float(f64),
   String(Rc<str>),
   //Bool(bool),
 }
 
 impl TokenValue {
-  pub fn to_int(self) -> Option<u32> {
+  pub fn to_int(self) -> Option<i64> {
     match self {
       TokenValue::Int(value) => Some(value),
       _ => None,
     }
   }
+  pub fn to_float(self) -> Option<f64> {
+    match self {
+      TokenValue::Float(value) => Some(value),
+      _ => None,
+    }
+  }
   pub fn to_string(self) -> Option<Rc<str>> {
     match self {
       TokenValue::String(value) => Some(value),
@@ -102,11 +227,16 @@ impl TokenValue {
   }
 }
 
-impl From<u32> for TokenValue {
-  fn from(value: u32) -> Self {
+impl From<i64> for TokenValue {
+  fn from(value: i64) -> Self {
     TokenValue::Int(value)
   }
 }
+impl From<f64> for TokenValue {
+  fn from(value: f64) -> Self {
+    TokenValue::Float(value)
+  }
+}
 //impl From<bool> for TokenValue {
 //  fn from(value: bool) -> Self {
 //    TokenValue::Bool(value)
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use crate::utils::{is_digit, is_letter};
+use crate::utils::{is_ident_continue, is_ident_start};
 
 #[derive(Debug)]
 pub struct Literal<'s>(&'s str);
@@ -22,7 +22,7 @@ impl<'s> Literal<'s> {
     let mut chars = text.chars();
     let len = match chars.next() {
       Some(c) => {
-        if is_letter(c) {
+        if is_ident_start(c) {
           c.len_utf8()
         } else {
           return None;
@@ -33,7 +33,7 @@ impl<'s> Literal<'s> {
 
     let len = text[len..]
       .chars()
-      .take_while(|c| is_letter(*c) || is_digit(*c))
+      .take_while(|c| is_ident_continue(*c))
       .fold(len, |n, c| n + c.len_utf8());
     Some(Literal(&text[0..len]))
   }
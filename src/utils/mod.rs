@@ -1,5 +1,23 @@
-pub fn is_letter(c: char) -> bool {
-  (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || "áéíóúÁÉÍÓÚñÑ_".contains(c)
+use std::borrow::Cow;
+
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// First char of an identifier: `XID_Start` from UAX #31, or `_`
+pub fn is_ident_start(c: char) -> bool {
+  c == '_' || unicode_ident::is_xid_start(c)
+}
+/// Rest of an identifier: `XID_Continue`, which includes digits, `_` and
+/// combining marks
+pub fn is_ident_continue(c: char) -> bool {
+  unicode_ident::is_xid_continue(c)
+}
+/// NFC form of `name`, so identifiers written with combining accents are the
+/// same as the ones with precomposed chars
+pub fn normalize_ident(name: &str) -> Cow<'_, str> {
+  match is_nfc_quick(name.chars()) {
+    IsNormalized::Yes => Cow::Borrowed(name),
+    _ => Cow::Owned(name.nfc().collect()),
+  }
 }
 pub fn is_digit(c: char) -> bool {
   c >= '0' && c <= '9'